@@ -9,7 +9,7 @@ use sdl2::{
     keyboard::Keycode,
     pixels::Color,
     rect::Rect,
-    render::TextureQuery
+    render::{BlendMode, TextureQuery}
 };
 
 use super::text::Text;
@@ -49,12 +49,14 @@ pub struct Window {
     canvas: sdl2::render::WindowCanvas,
     pub width: u32,
     pub height: u32,
+    vsync: bool,
     ctx: sdl2::Sdl,
     img_ctx: sdl2::image::Sdl2ImageContext
 }
 
 impl Window {
-    pub fn new(width: u32, height: u32) -> Self {
+    /// Opens a window. With `vsync` presenting a frame waits for the display refresh
+    pub fn new(width: u32, height: u32, vsync: bool) -> Self {
         let ctx = sdl2::init().unwrap();
         let img_ctx = sdl2::image::init(InitFlag::PNG | InitFlag::JPG).unwrap();
         let video_subsystem = ctx.video().unwrap();
@@ -64,15 +66,26 @@ impl Window {
             .build()
             .unwrap();
         
+        let canvas = if vsync {
+            window.into_canvas().present_vsync().build().unwrap()
+        } else {
+            window.into_canvas().build().unwrap()
+        };
+
         Window {
-            canvas: window.into_canvas().build().unwrap(),
+            canvas,
             width: width,
             height: height,
+            vsync,
             ctx: ctx,
             img_ctx: img_ctx
         }
     }
 
+    pub fn is_vsync(&self) -> bool {
+        self.vsync
+    }
+
     pub fn set_color(&mut self, clr: Color) {
         self.canvas.set_draw_color(clr);
     }
@@ -131,6 +144,16 @@ impl Window {
         Ok(())
     }
 
+    /// Same as `load_texture`, but blends the texture with a given opacity
+    pub fn load_texture_alpha(&mut self, png: &Path, src: Option<Rect>, dst: Option<Rect>, alpha: u8) -> Result<(), String> {
+        let texture_c = self.canvas.texture_creator();
+        let mut texture = texture_c.load_texture(png)?;
+        texture.set_blend_mode(BlendMode::Blend);
+        texture.set_alpha_mod(alpha);
+        self.canvas.copy(&texture, src, dst)?;
+        Ok(())
+    }
+
     pub fn texture_to_buffer(&mut self, texture: &sdl2::render::Texture, src: Option<Rect>, dst: Option<Rect>) {
         self.canvas.copy(texture, src, dst).unwrap();
     }
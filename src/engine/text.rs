@@ -1,6 +1,7 @@
 use std::borrow::Borrow;
 use std::path::Path;
 
+#[derive(Clone)]
 pub struct Text {
     content: String,
     pub pos_x: u32,
@@ -12,7 +13,7 @@ pub struct Text {
 }
 
 /// Returns a value that was cast to a string filled with leading zeros to match capacity.
-pub fn cast_with_capacity(value: u16, capacity: usize) -> String {
+pub fn cast_with_capacity(value: u64, capacity: usize) -> String {
     let mut string = value.to_string();
     if string.len() < capacity {
         string.insert_str(0, &"0".repeat(capacity - string.len()));
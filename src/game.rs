@@ -0,0 +1,16 @@
+//! Headless game core. Nothing in here knows about SDL or pixels, so it can be
+//! stepped and inspected by bots, tests and tools without opening a window.
+pub mod delay;
+pub mod field;
+pub mod garbage;
+pub mod grade;
+pub mod gravity;
+pub mod input;
+pub mod mode;
+pub mod randomizer;
+pub mod rotation;
+pub mod scoring;
+pub mod settings;
+pub mod sprint;
+pub mod tetromino;
+pub mod tspin;
@@ -0,0 +1,37 @@
+//! Entry and lock delays, all in ticks. ARE is the wait before the next piece spawns,
+//! the line clear delay adds to it when the locked piece cleared lines.
+
+/// Delays in effect at some level
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Delays {
+    pub are: u32,
+    pub line_clear: u32,
+    pub lock: u32,        // ticks a piece may rest on the stack before it locks
+}
+
+/// How delays follow the level
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DelayCurve {
+    Fixed(Delays),
+    Table(&'static [(u32, Delays)]),   // (first level, delays) steps in increasing order of levels
+}
+
+impl DelayCurve {
+    // TGM master mode: delays shrink section by section towards level 999
+    pub const MASTER: DelayCurve = DelayCurve::Table(&[
+        (0, Delays { are: 25, line_clear: 40, lock: 30 }),
+        (500, Delays { are: 25, line_clear: 25, lock: 30 }),
+        (600, Delays { are: 25, line_clear: 16, lock: 30 }),
+        (700, Delays { are: 16, line_clear: 12, lock: 30 }),
+        (800, Delays { are: 12, line_clear: 6, lock: 30 }),
+        (900, Delays { are: 12, line_clear: 6, lock: 17 }),
+    ]);
+
+    /// Returns delays at a given level. Levels below the first step get its delays
+    pub fn at(&self, level: u32) -> Delays {
+        match self {
+            DelayCurve::Fixed(delays) => *delays,
+            DelayCurve::Table(steps) => steps.iter().rev().find(|step| step.0 <= level).unwrap_or(&steps[0]).1,
+        }
+    }
+}
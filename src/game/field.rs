@@ -0,0 +1,1063 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+use rand::{rngs::StdRng, SeedableRng};
+
+use super::delay::{DelayCurve, Delays};
+use super::garbage::{self, GarbageRules};
+use super::gravity::{GravityCurve, Progression};
+use super::input::{Autoshift, Handling, Input, Shift};
+use super::randomizer::Randomizer;
+use super::rotation::{self, Kick, RotationSystem};
+use super::scoring::{Award, Placement, ScoringRules};
+use super::settings::{GameOverRules, Goal, Hold, LockReset, Modifiers, Settings, MAX_LOCK_RESETS, MAX_WIDTH, MIN_WIDTH};
+use super::tetromino::{Rotation, Shape, Tetromino};
+use super::tspin;
+
+/// Ticks the game logic runs per second
+pub const FRAME_RATE: u32 = 60;
+
+/// Contents of a board cell
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Block {
+    Piece(Shape),   // left by a locked piece
+    Garbage,        // raised from below
+}
+
+impl Block {
+    ///Returns a corresponding shift in texture
+    pub fn texture_offset(&self) -> u8 {
+        match self {
+            Block::Piece(shape) => shape.texture_offset(),
+            Block::Garbage => 7*18,
+        }
+    }
+}
+
+/// The condition that ended a game
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum GameOver {
+    BlockOut,   // a new piece overlapped the stack on spawn
+    LockOut,    // a piece locked completely above the visible field
+    TopOut,     // garbage pushed blocks past the buffer
+    Completed,  // the goal of the mode was reached
+}
+
+/// Game state before or after a placement, for undo and redo
+struct Snapshot {
+    tiles: HashMap<(i32,i32), Block>,
+    locked: HashMap<(i32,i32), u64>,
+    current: Shape,
+    queue: VecDeque<Shape>,
+    pocket: Option<Tetromino>,
+    pocketed: bool,
+    randomizer: Box<dyn Randomizer>,
+    rng: StdRng,
+    holes: garbage::Generator,
+    garbage_rng: StdRng,
+    combo: u32,
+    back_to_back: u32,
+    perfect_clears: u32,
+    garbage_lines: u32,
+    level: u32,
+    lines: u32,
+    score: u64,
+    pieces: u32,
+}
+
+pub struct Field {
+    pub tiles: HashMap<(i32,i32), Block>,
+    locked: HashMap<(i32,i32), u64>,   // tick each piece block was locked at
+    pocket: Option<Tetromino>,
+    current: Tetromino,
+    queue: VecDeque<Shape>,
+    cursor: usize,
+    width: usize,
+    height: usize,
+    buffer: i32,       // hidden rows above the visible field
+    rotation: &'static dyn RotationSystem,
+    ghost: bool,
+    hold: Hold,
+    hold_rotation: bool,
+    initial_hold: bool,
+    initial_rotation: bool,
+    held: Input,       // keys held in the last tick, read by IHS and IRS as a piece spawns
+    randomizer: Box<dyn Randomizer>,
+    rng: StdRng,
+    seed: u64,
+    fall: f32,         // rows of gravity gathered towards the next fall
+    gravity: GravityCurve,
+    progression: Progression,
+    start_level: u32,
+    delays: DelayCurve,
+    entry: u32,        // ticks left before the next piece spawns, no piece is in play meanwhile
+    lock_reset: LockReset,
+    handling: Handling,
+    autoshift: Autoshift,
+    scoring: &'static dyn ScoringRules,
+    awards: Vec<Award>,
+    placements: Vec<Placement>,
+    last_turn: Option<(Rotation, Kick)>,   // set while the last successful action was a rotation
+    combo: u32,                // clearing placements in a row
+    back_to_back: u32,         // difficult clears in a row, not broken by placements without lines
+    perfect_clears: u32,
+    soft_rows: u32,    // rows the current piece was soft dropped
+    hard_rows: u32,    // rows the current piece was hard or sonic dropped
+    lock_ticks: u32,   // ticks spent touching the stack
+    resets: u32,       // lock delay resets used by the current piece
+    lowest: i32,       // lowest row the current piece has reached
+    game_over: GameOverRules,
+    goal: Goal,
+    garbage: GarbageRules,
+    holes: garbage::Generator,
+    garbage_rng: StdRng,   // kept apart so garbage does not change the piece sequence
+    rise_ticks: u32,       // ticks since garbage last rose
+    garbage_lines: u32,    // cleared rows that held garbage
+    modifiers: Modifiers,
+    mirrored: u64,     // tick the stack was last flipped at
+    flips: u32,
+    undo: usize,                 // most placements kept for undo
+    history: Vec<Snapshot>,      // states before the placements that can be undone, oldest first
+    undone: Vec<Snapshot>,       // states after the placements that can be redone, latest undo last
+    spawn_score: u64,            // score before the current piece earned drop points
+    over: Option<GameOver>,
+    pub pocketed: bool,
+    pub level: u32,
+    pub lines: u32,
+    pub score: u64,
+    pieces: u32,       // pieces locked so far
+    frames: u64,       // ticks played so far
+}
+
+impl Field {
+    /// Creates a new instance with the board size of the settings. Games created with the same
+    /// seed and settings deal the same pieces and, given the same inputs, play out exactly the same
+    pub fn new(settings: Settings, seed: u64) -> Self {
+        let scale = if settings.modifiers.big {2} else {1};
+        let mut randomizer = settings.randomizer.build();
+        let mut rng = StdRng::seed_from_u64(seed);
+        let current = Tetromino::new(randomizer.next(&mut rng));
+        let queue = (0..settings.preview.clamp(1, 7)).map(|_| randomizer.next(&mut rng)).collect();
+        let mut field = Field {
+            tiles: HashMap::new(),
+            locked: HashMap::new(),
+            current,
+            queue,
+            randomizer,
+            rng,
+            seed,
+            width: (settings.width / scale).clamp(MIN_WIDTH, MAX_WIDTH),
+            height: (settings.height / scale).max(4),
+            buffer: (settings.buffer / scale).max(2) as i32,
+            rotation: settings.rotation.system(),
+            ghost: settings.ghost,
+            hold: settings.hold,
+            hold_rotation: settings.hold_rotation,
+            initial_hold: settings.initial_hold,
+            initial_rotation: settings.initial_rotation,
+            held: Input::default(),
+            pocket: None,
+            pocketed: false,
+            cursor: 0,
+            fall: 0.0,
+            gravity: settings.gravity,
+            progression: settings.progression,
+            start_level: settings.level,
+            delays: settings.delays,
+            entry: 0,
+            lock_reset: settings.lock_reset,
+            handling: settings.handling,
+            autoshift: Autoshift::default(),
+            scoring: settings.scoring.rules(),
+            awards: Vec::new(),
+            placements: Vec::new(),
+            last_turn: None,
+            combo: 0,
+            back_to_back: 0,
+            perfect_clears: 0,
+            soft_rows: 0,
+            hard_rows: 0,
+            lock_ticks: 0,
+            resets: 0,
+            lowest: 0,
+            game_over: settings.game_over,
+            goal: settings.goal,
+            garbage: settings.garbage,
+            holes: garbage::Generator::new(settings.garbage.holes),
+            garbage_rng: StdRng::seed_from_u64(seed.wrapping_add(1)),
+            rise_ticks: 0,
+            garbage_lines: 0,
+            modifiers: settings.modifiers,
+            mirrored: 0,
+            flips: 0,
+            undo: settings.undo,
+            history: Vec::new(),
+            undone: Vec::new(),
+            spawn_score: 0,
+            over: None,
+            level: settings.level,
+            lines: 0,
+            score: 0,
+            pieces: 0,
+            frames: 0,
+        };
+        field.raise_garbage(settings.garbage.rows.min(field.max_garbage()));
+        field.reset_piece();
+        field
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns how many minos wide and tall a board cell is drawn, 2 in big mode
+    pub fn scale(&self) -> usize {
+        if self.modifiers.big {2} else {1}
+    }
+
+    /// Returns a number of hidden rows above the visible field
+    pub fn buffer(&self) -> usize {
+        self.buffer as usize
+    }
+
+    /// Returns a number of ticks played so far
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// Returns the game time, counted in ticks so it is the same on every machine
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_secs(self.frames) / FRAME_RATE
+    }
+
+    /// Returns the game time left until a time goal, or None without one
+    pub fn remaining(&self) -> Option<Duration> {
+        match self.goal {
+            Goal::Time(secs) => Some(Duration::from_secs(secs as u64).saturating_sub(self.elapsed())),
+            _ => None,
+        }
+    }
+
+    /// Returns a number of cleared rows that held garbage
+    pub fn garbage_lines(&self) -> u32 {
+        self.garbage_lines
+    }
+
+    /// Returns a number of pieces locked so far
+    pub fn pieces(&self) -> u32 {
+        self.pieces
+    }
+
+    /// Returns locked pieces per second of game time
+    pub fn pieces_per_second(&self) -> f32 {
+        match self.frames {
+            0 => 0.0,
+            frames => self.pieces as f32 * FRAME_RATE as f32 / frames as f32,
+        }
+    }
+
+    /// Returns the seed the game was started from
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Returns the rotation system the game is played with
+    pub fn rotation_system(&self) -> &'static dyn RotationSystem {
+        self.rotation
+    }
+
+    /// Adds up the awards of a locked piece and keeps them for the HUD
+    fn score_placement(&mut self, placement: &Placement) {
+        for award in self.scoring.lock(placement) {
+            self.score += award.points;
+            self.awards.push(award);
+        }
+    }
+
+    /// Returns awards given since the last call, oldest first
+    pub fn take_awards(&mut self) -> Vec<Award> {
+        std::mem::take(&mut self.awards)
+    }
+
+    /// Returns a number of clearing placements in a row, 0 after a placement without lines
+    pub fn combo(&self) -> u32 {
+        self.combo
+    }
+
+    /// Returns a number of difficult clears (tetrises and spins) in a row
+    pub fn back_to_back(&self) -> u32 {
+        self.back_to_back
+    }
+
+    /// Returns a number of clears that left the board empty
+    pub fn perfect_clears(&self) -> u32 {
+        self.perfect_clears
+    }
+
+    /// Returns pieces locked since the last call, oldest first
+    pub fn take_placements(&mut self) -> Vec<Placement> {
+        std::mem::take(&mut self.placements)
+    }
+
+    /// Returns gravity at the current level, in cells per frame
+    pub fn gravity(&self) -> f32 {
+        self.gravity.at(self.level)
+    }
+
+    /// Returns ARE, line clear and lock delays at the current level
+    pub fn delays(&self) -> Delays {
+        self.delays.at(self.level)
+    }
+
+    /// Returns ticks left before the next piece spawns, 0 while a piece is in play
+    pub fn entry_delay(&self) -> u32 {
+        self.entry
+    }
+
+    /// Checks whether the current piece can be moved, turned, dropped or held
+    fn active(&self) -> bool {
+        self.over.is_none() && self.entry == 0
+    }
+
+    /// Returns the condition the game ended with, or None while it goes on
+    pub fn game_over(&self) -> Option<GameOver> {
+        self.over
+    }
+
+    /// Returns true once the game has ended
+    pub fn is_over(&self) -> bool {
+        self.over.is_some()
+    }
+
+    /// Removes every filled row, the buffer included, shifting the rows above down.
+    /// Returns a number of rows removed
+    pub fn check_lines(&mut self) -> u32 {
+        let filled: Vec<i32> = (-self.buffer..self.height as i32)
+            .filter(|y| (0..self.width as i32).all(|x| self.tiles.contains_key(&(x, *y))))
+            .collect();
+        if filled.is_empty() {
+            return 0;
+        }
+        self.garbage_lines += filled.iter()
+            .filter(|y| (0..self.width as i32).any(|x| self.tiles.get(&(x, **y)) == Some(&Block::Garbage)))
+            .count() as u32;
+        self.remap(|(x, y)| {
+            let shift = filled.iter().filter(|row| **row > y).count() as i32; // rows removed below this one
+            Some((x, y + shift)).filter(|_| !filled.contains(&y))
+        });
+        filled.len() as u32
+    }
+
+    /// Returns a ref to the current piece
+    pub fn current_piece(&self) -> Option<&Tetromino> {
+        Some(&self.current).filter(|_| self.entry == 0)
+    }
+
+    /// Returns upcoming figures, the next one first
+    pub fn queue(&self) -> &VecDeque<Shape> {
+        &self.queue
+    }
+
+    /// Returns where the current piece would land if dropped straight down,
+    /// or None when the ghost piece is turned off
+    pub fn ghost(&self) -> Option<Tetromino> {
+        if !self.ghost || self.entry > 0 {
+            return None;
+        }
+        let mut ghost = self.current;
+        while self.fits(&ghost.moved(0, 1)) {
+            ghost = ghost.moved(0, 1);
+        }
+        Some(ghost)
+    }
+
+    /// Returns a ref to the pocketed piece, if any
+    pub fn pocketed_piece(&self) -> Option<&Tetromino> {
+        self.pocket.as_ref()
+    }
+
+    /// Checks whether a cell is taken by the stack or lies outside of the walls, the floor or the buffer
+    pub fn occupied(&self, cell: (i32, i32)) -> bool {
+        cell.0 < 0 || cell.0 >= self.width as i32 || cell.1 >= self.height as i32 || cell.1 < -self.buffer
+            || self.tiles.contains_key(&cell)
+    }
+
+    /// Checks whether a piece fits inside the walls and does not overlap the stack
+    pub fn fits(&self, piece: &Tetromino) -> bool {
+        !piece.get_tiles_pos().iter().any(|c| self.occupied(*c))
+    }
+
+    /// Tries to move the current piece by (dx, dy) cells. Returns true on success
+    pub fn make_move(&mut self, dx: i32, dy: i32) -> bool {
+        let moved = self.current.moved(dx, dy);
+        if self.active() && self.fits(&moved) {
+            self.current = moved;
+            self.last_turn = None;
+            self.reset_lock();
+            return true;
+        }
+        false
+    }
+
+    /// Checks whether the current piece rests on the stack or the floor
+    pub fn is_grounded(&self) -> bool {
+        !self.fits(&self.current.moved(0, 1))
+    }
+
+    /// Applies the lock delay reset policy after the current piece moved or turned
+    fn reset_lock(&mut self) {
+        let y = self.current.position().1;
+        if y > self.lowest {
+            // falling to a new row always starts the lock delay over
+            self.lowest = y;
+            self.lock_ticks = 0;
+            self.resets = 0;
+            return;
+        }
+        if self.lock_ticks == 0 {
+            return;
+        }
+        match self.lock_reset {
+            LockReset::Infinite => self.lock_ticks = 0,
+            LockReset::Step if self.resets < MAX_LOCK_RESETS => {
+                self.lock_ticks = 0;
+                self.resets += 1;
+            }
+            _ => (),
+        }
+    }
+
+    /// Spawns the current piece and starts the lock delay over for it. Pieces spawn in the buffer
+    /// right above the visible field, turned the way they are, and drop one row into view if there is room
+    fn reset_piece(&mut self) {
+        let angle = self.current.angle();
+        self.current.set_default_pos(self.width, self.rotation);
+        for _ in 0..angle {
+            self.current = self.current.rotated(Rotation::Right, self.rotation);
+        }
+        let bottom = self.current.get_tiles_pos().iter().map(|c| c.1).max().unwrap();
+        self.current = self.current.moved(0, -1 - bottom);
+        self.check_spawn();
+        if self.over.is_none() && self.fits(&self.current.moved(0, 1)) {
+            self.current = self.current.moved(0, 1);
+        }
+        self.lowest = self.current.position().1;
+        self.lock_ticks = 0;
+        self.resets = 0;
+        self.soft_rows = 0;
+        self.hard_rows = 0;
+        self.last_turn = None;
+        self.fall = 0.0;
+        self.spawn_score = self.score;
+    }
+
+    /// Ends the game with a block out if the spawned piece overlaps the stack.
+    /// Without the block out rule the piece is lifted until it clears the stack,
+    /// and the stack is cleared when the buffer has no room left
+    fn check_spawn(&mut self) {
+        if self.fits(&self.current) {
+            return;
+        }
+        if self.game_over.block_out {
+            self.over = Some(GameOver::BlockOut);
+            return;
+        }
+        let spawn = self.current;
+        self.lift_piece();
+        if !self.fits(&self.current) {
+            self.remap(|_| None);
+            self.current = spawn;
+        }
+    }
+
+    /// Moves the current piece up until it no longer overlaps the stack or reaches the top of the buffer
+    fn lift_piece(&mut self) {
+        while !self.fits(&self.current) && self.current.get_tiles_pos().iter().all(|c| c.1 > -self.buffer) {
+            self.current = self.current.moved(0, -1);
+        }
+    }
+
+    /// Raises the stack by a number of garbage rows with holes placed by the garbage rules
+    pub fn raise_garbage(&mut self, rows: u32) {
+        let holes: Vec<usize> = (0..rows).map(|_| self.holes.next(self.width, &mut self.garbage_rng)).collect();
+        self.add_garbage(&holes);
+    }
+
+    /// Moves every block of the stack to a new cell, or removes it where `f` gives None
+    fn remap<F: Fn((i32, i32)) -> Option<(i32, i32)>>(&mut self, f: F) {
+        let tiles = std::mem::take(&mut self.tiles);
+        self.tiles = tiles.into_iter().filter_map(|(c, block)| f(c).map(|c| (c, block))).collect();
+        let locked = std::mem::take(&mut self.locked);
+        self.locked = locked.into_iter().filter_map(|(c, tick)| f(c).map(|c| (c, tick))).collect();
+    }
+
+    /// Checks whether a block of the stack is drawn. Invisible stacks hide every block,
+    /// fading ones hide pieces some time after they locked
+    pub fn shown(&self, cell: (i32, i32)) -> bool {
+        if self.modifiers.invisible {
+            return false;
+        }
+        let fading = self.modifiers.fading as u64;
+        self.locked.get(&cell).is_none_or(|tick| fading == 0 || self.frames - tick < fading)
+    }
+
+    /// Returns true if the outline of the stack is drawn in place of its blocks
+    pub fn outlined(&self) -> bool {
+        self.modifiers.invisible
+    }
+
+    /// Returns a number of times the stack was flipped by the mirror modifier
+    pub fn flips(&self) -> u32 {
+        self.flips
+    }
+
+    /// Flips the stack left to right once the mirror timer ran out, between two pieces
+    fn mirror(&mut self) {
+        if self.modifiers.mirror == 0 || self.frames - self.mirrored < self.modifiers.mirror as u64 {
+            return;
+        }
+        let right = self.width as i32 - 1;
+        self.remap(|(x, y)| Some((right - x, y)));
+        self.mirrored = self.frames;
+        self.flips += 1;
+    }
+
+    /// Returns a number of garbage rows the board takes at the start and by refills,
+    /// leaving the top four visible rows free for pieces to spawn and turn
+    fn max_garbage(&self) -> u32 {
+        self.height.saturating_sub(4) as u32
+    }
+
+    /// Returns a number of rows that hold garbage
+    pub fn garbage_rows(&self) -> u32 {
+        let mut rows: Vec<i32> = self.tiles.iter().filter(|(_, b)| **b == Block::Garbage).map(|(c, _)| c.1).collect();
+        rows.sort_unstable();
+        rows.dedup();
+        rows.len() as u32
+    }
+
+    /// Raises the stack by one garbage row per entry of `holes`, each open at the given column.
+    /// Blocks pushed past the buffer end the game with a top out, or vanish without the top out rule
+    pub fn add_garbage(&mut self, holes: &[usize]) {
+        if self.over.is_some() || holes.is_empty() {
+            return;
+        }
+        let rows = holes.len() as i32;
+        self.remap(|(x, y)| Some((x, y - rows)));
+        for (i, hole) in holes.iter().enumerate() {
+            let y = self.height as i32 - rows + i as i32;
+            for x in (0..self.width as i32).filter(|x| *x != *hole as i32) {
+                self.tiles.insert((x, y), Block::Garbage);
+            }
+        }
+        if self.tiles.keys().any(|c| c.1 < -self.buffer) {
+            if self.game_over.top_out {
+                self.over = Some(GameOver::TopOut);
+                return;
+            }
+            let buffer = self.buffer;
+            self.remap(|c| Some(c).filter(|c| c.1 >= -buffer));
+        }
+        self.lift_piece(); // the current piece is pushed up along with the stack
+    }
+
+    /// Tries to rotate the current piece with the chosen rotation system.
+    /// Returns the kick that was used, or None if the piece could not turn
+    pub fn rotate(&mut self, direction: Rotation) -> Option<Kick> {
+        if !self.active() {
+            return None;
+        }
+        let (turned, kick) = rotation::rotate(self.rotation, &self.current, direction, |c| self.occupied(c))?;
+        self.current = turned;
+        self.last_turn = Some((direction, kick));
+        self.reset_lock();
+        self.autoshift.cut(&self.handling);
+        Some(kick)
+    }
+
+    /// Takes the next piece off the queue and refills it from the randomizer
+    fn new_piece(&mut self) -> Tetromino {
+        let shape = self.randomizer.next(&mut self.rng);
+        self.queue.push_back(shape);
+        Tetromino::new(self.queue.pop_front().unwrap())
+    }
+
+    /// Moves the current piece into the stack and clears the lines it completed. Returns a number of lines cleared
+    pub fn lock_piece(&mut self) -> u32 {
+        let spin = tspin::detect(&self.current, self.last_turn, |c| self.occupied(c));
+        self.pieces += 1;
+        let block = Block::Piece(self.current.get_shape());
+        for c in self.current.get_tiles_pos().iter() {
+            self.tiles.insert(*c, block);
+            self.locked.insert(*c, self.frames);
+        }
+        let n = self.check_lines();
+        self.lines += n;
+        let level = self.progression.clear(self.level, self.start_level, n, self.lines);
+        let mut placement = Placement {
+            lines: n,
+            spin,
+            level: self.level,
+            combo: self.combo,
+            back_to_back: false,
+            perfect: n > 0 && self.tiles.is_empty(),
+            soft_rows: self.soft_rows,
+            hard_rows: self.hard_rows,
+        };
+        if placement.is_difficult() {
+            placement.back_to_back = self.back_to_back > 0;
+            self.back_to_back += 1;
+        } else if n > 0 {
+            self.back_to_back = 0;
+        }
+        self.combo = if n > 0 {self.combo + 1} else {0};
+        if placement.perfect {
+            self.perfect_clears += 1;
+        }
+        self.level = level;
+        self.score_placement(&placement);
+        self.placements.push(placement);
+        n
+    }
+
+    /// Handles spawning of the new piece
+    pub fn next_piece(&mut self) {
+        self.current = self.new_piece();
+        self.cursor += 1;
+        self.mirror();
+        self.level = self.progression.piece(self.level);
+        self.reset_piece();
+        self.pocketed = false;
+        if self.over.is_none() {
+            self.initial_actions();
+        }
+        if self.gravity() >= 20.0 && self.over.is_none() {
+            self.drop_down(); // at 20G pieces appear already on the floor
+        }
+    }
+
+    /// Handles the logic of pocketing a piece, as often as the hold rule allows
+    pub fn pocket(&mut self) {
+        let allowed = match self.hold {
+            Hold::Disabled => false,
+            Hold::Classic => !self.pocketed,
+            Hold::Infinite => true,
+        };
+        if allowed && self.active() {
+            let held = if self.hold_rotation {self.current} else {Tetromino::new(self.current.get_shape())};
+            match self.pocket {
+                Some(pocket) => self.current = pocket,
+                None => self.current = self.new_piece(),
+            }
+            self.reset_piece();
+            self.pocket = Some(held);
+            self.pocketed = true;
+        }
+    }
+
+    /// Applies the hold (IHS) and the rotation (IRS) whose keys are held as a piece spawns,
+    /// at its spawn position before anything else moves it
+    fn initial_actions(&mut self) {
+        if self.initial_hold && self.held.hold {
+            self.pocket();
+        }
+        if let Some(direction) = self.held.rotate.filter(|_| self.initial_rotation) {
+            // turns in place without cutting the autorepeat, the piece has not been handled yet
+            if let Some((turned, _)) = rotation::rotate(self.rotation, &self.current, direction, |c| self.occupied(c)) {
+                self.current = turned;
+            }
+        }
+    }
+
+    /// Advances the game by one tick with the keys held in it. The piece shifts according
+    /// to the autorepeat, falls with the gravity of the current level (sped up by the soft drop)
+    /// and locks once it has rested on the stack for the lock delay. After a lock the next piece
+    /// waits out the entry delay (ARE, plus the line clear delay). Returns true if a piece was locked.
+    pub fn tick(&mut self, input: &Input) -> bool {
+        if self.over.is_some() {
+            return false;
+        }
+        self.frames += 1;
+        self.held = *input;
+        if let Goal::Time(secs) = self.goal {
+            if self.frames >= secs as u64 * FRAME_RATE as u64 {
+                self.over = Some(GameOver::Completed);
+                return false;
+            }
+        }
+        if self.garbage.rise > 0 {
+            self.rise_ticks += 1;
+            if self.rise_ticks >= self.garbage.rise {
+                self.rise_ticks = 0;
+                self.raise_garbage(1);
+                if self.over.is_some() {
+                    return false;
+                }
+            }
+        }
+        if self.entry > 0 {
+            self.entry -= 1;
+            if self.entry > 0 {
+                self.autoshift.update(input, &self.handling); // DAS charges during the delay
+                return false;
+            }
+            self.next_piece();
+            if self.over.is_some() {
+                return false;
+            }
+        }
+        match self.autoshift.update(input, &self.handling) {
+            Shift::Step(dx) => {self.make_move(dx, 0);}
+            Shift::Wall(dx) => while self.make_move(dx, 0) {},
+            Shift::Stay => (),
+        }
+        let mut gravity = self.gravity();
+        if input.soft_drop {
+            // without gravity the soft drop still falls as fast as it would at a row per second
+            gravity = (gravity * self.handling.soft_drop).max(self.handling.soft_drop / FRAME_RATE as f32);
+        }
+        self.fall += gravity;
+        while self.fall >= 1.0 {
+            self.fall -= 1.0;
+            if !self.make_move(0, 1) {
+                self.fall = 0.0;
+                break;
+            }
+            if input.soft_drop {
+                self.soft_rows += 1;
+                self.score += self.scoring.drop(1, false);
+            }
+        }
+        if !self.is_grounded() {
+            return false;
+        }
+        self.lock_ticks += 1;
+        let out_of_resets = self.lock_reset == LockReset::Step && self.resets >= MAX_LOCK_RESETS;
+        if self.lock_ticks < self.delays().lock && !out_of_resets {
+            return false;
+        }
+        self.lock();
+        true
+    }
+
+    /// Locks the current piece where it is and spawns the next one. A piece locking
+    /// completely above the visible field ends the game with a lock out
+    fn lock(&mut self) {
+        if self.game_over.lock_out && self.current.get_tiles_pos().iter().all(|c| c.1 < 0) {
+            self.over = Some(GameOver::LockOut);
+            return;
+        }
+        if self.undo > 0 {
+            if self.history.len() == self.undo {
+                self.history.remove(0);
+            }
+            self.history.push(self.snapshot());
+            self.undone.clear();
+        }
+        let lines = self.lock_piece();
+        match self.goal {
+            Goal::Lines(goal) if self.lines >= goal => {
+                self.over = Some(GameOver::Completed);
+                return;
+            }
+            Goal::Garbage(goal) if self.garbage_lines >= goal => {
+                self.over = Some(GameOver::Completed);
+                return;
+            }
+            Goal::Level(goal) if self.level >= goal => {
+                self.over = Some(GameOver::Completed);
+                return;
+            }
+            Goal::Garbage(goal) => {
+                // refill the garbage cleared, but no more than is left to clear
+                let wanted = self.garbage.rows.min(goal - self.garbage_lines).min(self.max_garbage());
+                self.raise_garbage(wanted.saturating_sub(self.garbage_rows()));
+                if self.over.is_some() {
+                    return;
+                }
+            }
+            _ => (),
+        }
+        let delays = self.delays();
+        self.entry = delays.are + if lines > 0 {delays.line_clear} else {0};
+        if self.entry == 0 {
+            self.next_piece();
+        }
+    }
+
+    /// Moves the current piece straight down as far as it goes. Returns a number of rows dropped
+    fn drop_down(&mut self) -> u32 {
+        let mut rows = 0;
+        while self.make_move(0, 1) {
+            rows += 1;
+        }
+        rows
+    }
+
+    /// Drops the current piece onto the stack and locks it in the same tick
+    pub fn hard_drop(&mut self) {
+        if !self.active() {
+            return;
+        }
+        let rows = self.drop_down();
+        self.hard_rows += rows;
+        self.score += self.scoring.drop(rows, true);
+        self.lock();
+    }
+
+    /// Drops the current piece onto the stack without locking it, so it can still slide.
+    /// Scores the same as a soft drop over that distance
+    pub fn sonic_drop(&mut self) {
+        if !self.active() {
+            return;
+        }
+        let rows = self.drop_down();
+        self.hard_rows += rows;
+        self.score += self.scoring.drop(rows, false);
+    }
+
+    /// Takes back the last placement: the board, the current piece, the queue and the hold
+    /// return to how they were before it. Returns false if there is nothing to undo
+    pub fn undo(&mut self) -> bool {
+        match self.history.pop() {
+            Some(before) => {
+                let after = self.snapshot();
+                self.undone.push(after);
+                self.restore(before);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Places the last undone placement again. Returns false if there is nothing to redo
+    pub fn redo(&mut self) -> bool {
+        match self.undone.pop() {
+            Some(after) => {
+                let before = self.snapshot();
+                self.history.push(before);
+                self.restore(after);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            tiles: self.tiles.clone(),
+            locked: self.locked.clone(),
+            current: self.current.get_shape(),
+            queue: self.queue.clone(),
+            pocket: self.pocket,
+            pocketed: self.pocketed,
+            randomizer: self.randomizer.snapshot(),
+            rng: self.rng.clone(),
+            holes: self.holes.clone(),
+            garbage_rng: self.garbage_rng.clone(),
+            combo: self.combo,
+            back_to_back: self.back_to_back,
+            perfect_clears: self.perfect_clears,
+            garbage_lines: self.garbage_lines,
+            level: self.level,
+            lines: self.lines,
+            score: self.spawn_score,
+            pieces: self.pieces,
+        }
+    }
+
+    /// Brings back a snapshot with its current piece spawned anew
+    fn restore(&mut self, s: Snapshot) {
+        self.tiles = s.tiles;
+        self.locked = s.locked;
+        self.current = Tetromino::new(s.current);
+        self.queue = s.queue;
+        self.pocket = s.pocket;
+        self.pocketed = s.pocketed;
+        self.randomizer = s.randomizer;
+        self.rng = s.rng;
+        self.holes = s.holes;
+        self.garbage_rng = s.garbage_rng;
+        self.combo = s.combo;
+        self.back_to_back = s.back_to_back;
+        self.perfect_clears = s.perfect_clears;
+        self.garbage_lines = s.garbage_lines;
+        self.level = s.level;
+        self.lines = s.lines;
+        self.score = s.score;
+        self.pieces = s.pieces;
+        self.over = None;
+        self.entry = 0;
+        self.reset_piece();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::mode::Mode;
+
+    type State = (HashMap<(i32, i32), Block>, u64, u32, VecDeque<Shape>, Option<Tetromino>, Option<Tetromino>);
+
+    fn state(field: &Field) -> State {
+        (field.tiles.clone(), field.score, field.lines, field.queue.clone(), field.current_piece().copied(), field.pocket)
+    }
+
+    /// Keys held on a given tick, the same for every game
+    fn input(tick: u32) -> Input {
+        Input {
+            left: tick % 90 < 20,
+            right: tick % 130 < 15,
+            soft_drop: tick % 40 < 10,
+            hold: tick.is_multiple_of(300),
+            rotate: match tick % 70 {
+                0 => Some(Rotation::Right),
+                35 => Some(Rotation::Left),
+                _ => None,
+            },
+        }
+    }
+
+    #[test]
+    fn same_seed_and_inputs_play_the_same_game() {
+        let mut games = [Field::new(Mode::Marathon.settings(), 42), Field::new(Mode::Marathon.settings(), 42)];
+        for tick in 0..3000 {
+            for field in games.iter_mut() {
+                field.tick(&input(tick));
+                if let Some(r) = input(tick).rotate {
+                    field.rotate(r);
+                }
+                if tick % 60 == 59 {
+                    field.hard_drop();
+                }
+            }
+        }
+        assert!(games[0].pieces() > 20);
+        assert_eq!(state(&games[0]), state(&games[1]));
+        assert_eq!(games[0].frames(), games[1].frames());
+        assert_eq!(games[0].game_over(), games[1].game_over());
+    }
+
+    #[test]
+    fn t_spin_double_scores_1200() {
+        let mut field = Field::new(Mode::Marathon.settings(), 7);
+        let system = field.rotation_system();
+        // a T-slot at the bottom of the board, roofed on its left
+        let slot = [(3, 18), (4, 18), (5, 18), (4, 19)];
+        for x in 0..10 {
+            for y in 18..20 {
+                if !slot.contains(&(x, y)) {
+                    field.tiles.insert((x, y), Block::Garbage);
+                }
+            }
+        }
+        field.tiles.insert((3, 17), Block::Garbage);
+
+        // T pointing down, moved into the slot, then turned back a quarter to rotate in from above
+        let down = Tetromino::new(Shape::T).rotated(Rotation::Half, system);
+        let (min_x, min_y) = down.get_tiles_pos().iter().fold((i32::MAX, i32::MAX), |m, c| (m.0.min(c.0), m.1.min(c.1)));
+        let down = down.moved(3 - min_x, 18 - min_y);
+        field.current = down.rotated(Rotation::Left, system);
+        assert!(field.fits(&field.current));
+
+        assert!(field.rotate(Rotation::Right).is_some());
+        assert_eq!(field.current, down);
+        field.hard_drop();
+        assert_eq!(field.lines, 2);
+        assert_eq!(field.score, 1200);
+        assert!(field.take_awards().iter().any(|a| a.label == "T-SPIN DOUBLE" && a.points == 1200));
+    }
+
+    #[test]
+    fn undo_and_redo_restore_placements() {
+        let mut field = Field::new(Mode::Zen.settings(), 3);
+        for dx in [-3, 0, 3].iter() {
+            field.make_move(*dx, 0);
+            field.hard_drop();
+        }
+        let before = state(&field);
+        field.make_move(-1, 0);
+        field.hard_drop();
+        let after = state(&field);
+        assert_ne!(before, after);
+
+        assert!(field.undo());
+        assert_eq!(state(&field), before);
+        assert!(field.redo());
+        assert_eq!(state(&field), after);
+        assert!(!field.redo());
+    }
+
+    #[test]
+    fn dig_garbage_leaves_room_to_spawn() {
+        for height in [6, 10, 20].iter() {
+            let mut field = Field::new(Settings { height: *height, ..Mode::Dig(40).settings() }, 5);
+            assert_eq!(field.garbage_rows(), (*height as u32 - 4).min(10));
+            assert!(!field.is_over());
+            field.hard_drop();
+            assert!(!field.is_over());
+        }
+        let big = Field::new(Settings { modifiers: Modifiers { big: true, ..Modifiers::default() }, ..Mode::Dig(40).settings() }, 5);
+        assert_eq!(big.garbage_rows(), 6);
+    }
+
+    /// Sonic drops a piece and returns the ticks it takes to lock, moving it sideways
+    /// after every tick while `moves` allows
+    fn ticks_to_lock(lock_reset: LockReset, moves: u32) -> u32 {
+        let mut field = Field::new(Settings { lock_reset, ..Mode::Marathon.settings() }, 1);
+        field.sonic_drop();
+        let mut ticks = 0;
+        while ticks < 1000 {
+            ticks += 1;
+            if field.tick(&Input::default()) {
+                break;
+            }
+            if ticks <= moves {
+                field.make_move(if ticks % 2 == 0 {1} else {-1}, 0);
+            }
+        }
+        ticks
+    }
+
+    #[test]
+    fn pieces_lock_after_the_lock_delay() {
+        let lock = Mode::Marathon.settings().delays.at(1).lock;
+        assert_eq!(ticks_to_lock(LockReset::Never, 0), lock);
+        assert_eq!(ticks_to_lock(LockReset::Never, 10), lock);
+    }
+
+    #[test]
+    fn step_reset_stops_after_the_reset_cap() {
+        // every move resets the delay once a tick has passed, and the tick after the last reset locks
+        assert_eq!(ticks_to_lock(LockReset::Step, 100), MAX_LOCK_RESETS + 1);
+        let lock = Mode::Marathon.settings().delays.at(1).lock;
+        assert_eq!(ticks_to_lock(LockReset::Step, 5), 5 + lock);
+    }
+
+    #[test]
+    fn infinite_reset_keeps_a_moving_piece_from_locking() {
+        assert_eq!(ticks_to_lock(LockReset::Infinite, 1000), 1000);
+    }
+
+    #[test]
+    fn check_lines_collapses_rows_apart_and_in_the_buffer() {
+        let mut field = Field::new(Mode::Marathon.settings(), 1);
+        for x in 0..10 {
+            for y in [-1, 17, 19].iter() {
+                field.tiles.insert((x, *y), Block::Garbage);
+            }
+        }
+        field.tiles.insert((0, 18), Block::Piece(Shape::I));
+        field.tiles.insert((2, 16), Block::Piece(Shape::T));
+        field.tiles.insert((5, -2), Block::Piece(Shape::O));
+        assert_eq!(field.check_lines(), 3);
+        let mut left: Vec<((i32, i32), Block)> = field.tiles.iter().map(|(c, b)| (*c, *b)).collect();
+        left.sort_by_key(|t| t.0);
+        assert_eq!(left, vec![((0, 19), Block::Piece(Shape::I)), ((2, 18), Block::Piece(Shape::T)), ((5, 1), Block::Piece(Shape::O))]);
+        assert_eq!(field.garbage_lines(), 3);
+    }
+}
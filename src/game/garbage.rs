@@ -0,0 +1,80 @@
+//! Garbage rows for digging practice. Every row is full but for one hole.
+
+use rand::{Rng, RngCore};
+
+/// Where the holes of consecutive garbage rows go
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Holes {
+    Random,       // any column, independently for every row
+    Clean,        // all rows share one column
+    Messy(u32),   // percent chance that a row moves its hole away from the one of the row before
+}
+
+/// How garbage fills a game
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GarbageRules {
+    pub rows: u32,        // garbage rows at the start, refilled up to this while a garbage goal lasts
+    pub holes: Holes,
+    pub rise: u32,        // ticks between two rows rising from below, 0 -- garbage never rises
+}
+
+/// Picks the hole of every new garbage row
+#[derive(Clone)]
+pub struct Generator {
+    holes: Holes,
+    last: Option<usize>,
+}
+
+impl Generator {
+    pub fn new(holes: Holes) -> Self {
+        Generator { holes, last: None }
+    }
+
+    /// Returns the hole column of the next row on a board of a given width
+    pub fn next(&mut self, width: usize, rng: &mut dyn RngCore) -> usize {
+        let hole = match (self.holes, self.last) {
+            (Holes::Clean, Some(last)) => last,
+            (Holes::Messy(messiness), Some(last)) if width > 1 && rng.gen_range(0, 100) < messiness => {
+                // any column but the last one
+                (last + rng.gen_range(1, width)) % width
+            }
+            (Holes::Messy(_), Some(last)) => last,
+            _ => rng.gen_range(0, width),
+        };
+        self.last = Some(hole);
+        hole
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn holes(holes: Holes, rows: usize) -> Vec<usize> {
+        let mut generator = Generator::new(holes);
+        let mut rng = StdRng::seed_from_u64(9);
+        (0..rows).map(|_| generator.next(10, &mut rng)).collect()
+    }
+
+    #[test]
+    fn clean_garbage_keeps_one_column() {
+        let rows = holes(Holes::Clean, 20);
+        assert!(rows.iter().all(|h| *h == rows[0]));
+    }
+
+    #[test]
+    fn messy_garbage_moves_its_hole_by_chance() {
+        assert!(holes(Holes::Messy(0), 20).windows(2).all(|w| w[0] == w[1]));
+        assert!(holes(Holes::Messy(100), 20).windows(2).all(|w| w[0] != w[1]));
+        let half = holes(Holes::Messy(50), 200).windows(2).filter(|w| w[0] != w[1]).count();
+        assert!(half > 60 && half < 140, "{}", half);
+    }
+
+    #[test]
+    fn holes_stay_on_the_board() {
+        for kind in [Holes::Random, Holes::Clean, Holes::Messy(30)].iter() {
+            assert!(holes(*kind, 100).iter().all(|h| *h < 10));
+        }
+    }
+}
@@ -0,0 +1,54 @@
+//! TGM master grades. The grade follows the score, and Grand Master also asks for
+//! checkpoints reached in time on the way to level 999.
+
+use std::time::Duration;
+
+use super::field::{Field, GameOver};
+
+// (lowest score, grade) in increasing order of scores
+const GRADES: [(u64, &str); 18] = [
+    (0, "9"), (400, "8"), (800, "7"), (1400, "6"), (2000, "5"), (3500, "4"),
+    (5500, "3"), (8000, "2"), (12000, "1"), (16000, "S1"), (22000, "S2"), (30000, "S3"),
+    (40000, "S4"), (52000, "S5"), (66000, "S6"), (82000, "S7"), (100000, "S8"), (120000, "S9"),
+];
+
+// (level, lowest score, latest time in seconds) to stay on the way to Grand Master
+const CHECKPOINTS: [(u32, u64, u64); 3] = [(300, 12000, 4 * 60 + 15), (500, 40000, 7 * 60 + 30), (999, 126000, 13 * 60 + 30)];
+
+/// Returns the grade earned with a score
+pub fn grade(score: u64) -> &'static str {
+    GRADES.iter().rev().find(|g| g.0 <= score).unwrap_or(&GRADES[0]).1
+}
+
+/// Follows a game through the Grand Master checkpoints
+#[derive(Default)]
+pub struct Grading {
+    passed: usize,    // checkpoints reached with the score and in time
+    failed: bool,
+}
+
+impl Grading {
+    /// Checks the checkpoints the game has reached since the last call
+    pub fn update(&mut self, field: &Field) {
+        while let Some(&(level, score, secs)) = CHECKPOINTS.get(self.passed).filter(|_| !self.failed) {
+            if field.level < level {
+                return;
+            }
+            if field.score < score || field.elapsed() > Duration::from_secs(secs) {
+                self.failed = true;
+                return;
+            }
+            self.passed += 1;
+        }
+    }
+
+    /// Returns the grade of the game so far
+    pub fn grade(&self, field: &Field) -> &'static str {
+        let completed = field.game_over() == Some(GameOver::Completed);
+        if completed && !self.failed && self.passed == CHECKPOINTS.len() {
+            "GM"
+        } else {
+            grade(field.score)
+        }
+    }
+}
@@ -0,0 +1,93 @@
+//! Level progression and gravity curves. Gravity is measured in cells per frame (G):
+//! 1/60 G falls a row every second, 1G a row every frame and 20G drops a piece to the floor at once.
+
+/// How cleared lines turn into levels
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Progression {
+    Fixed(u32),   // a new level every given number of lines
+    Nes,          // NES: the first level up depends on the start level, then every 10 lines
+    Static,       // the game stays at its start level
+    Tgm,          // TGM: a level for every piece and every line, held at the end of a section until a line clear
+}
+
+impl Progression {
+    /// Returns the level reached after clearing a number of lines from a given start level
+    pub fn level(&self, start: u32, lines: u32) -> u32 {
+        match self {
+            Progression::Fixed(n) => start + lines / n,
+            Progression::Nes => {
+                let first = (start * 10 + 10).min(100.max((start * 10).saturating_sub(50)));
+                if lines < first {start} else {start + 1 + (lines - first) / 10}
+            }
+            Progression::Static => start,
+            Progression::Tgm => (start + lines).min(MAX_TGM_LEVEL),
+        }
+    }
+
+    /// Returns the level after a piece spawns at a given level. Only TGM counts pieces
+    pub fn piece(&self, level: u32) -> u32 {
+        match self {
+            Progression::Tgm if level % 100 != 99 && level < MAX_TGM_LEVEL - 1 => level + 1,
+            _ => level,
+        }
+    }
+
+    /// Returns the level after a piece cleared a number of lines, `total` lines in all
+    pub fn clear(&self, level: u32, start: u32, lines: u32, total: u32) -> u32 {
+        match self {
+            Progression::Tgm => (level + lines).min(MAX_TGM_LEVEL),
+            _ => self.level(start, total).max(level),
+        }
+    }
+}
+
+/// TGM games end at this level
+pub const MAX_TGM_LEVEL: u32 = 999;
+
+/// Gravity curves a game can be set up with
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum GravityCurve {
+    Table(&'static [(u32, f32)]),   // (first level, cells per frame) steps in increasing order of levels
+    Fixed(f32),                     // the same gravity at every level, 0 -- pieces only fall when dropped
+}
+
+impl GravityCurve {
+    pub const GUIDELINE: GravityCurve = GravityCurve::Table(&[
+        (1, 0.01667), (2, 0.02102), (3, 0.02698), (4, 0.03526), (5, 0.04692),
+        (6, 0.06361), (7, 0.08787), (8, 0.1237), (9, 0.17753), (10, 0.2598),
+        (11, 0.38781), (12, 0.59065), (13, 0.91811), (14, 1.45696), (15, 2.36118),
+        (16, 3.9091), (17, 6.61354), (18, 11.43794), (19, 20.0),
+    ]);
+
+    pub const NES: GravityCurve = GravityCurve::Table(&[
+        (0, 1.0 / 48.0), (1, 1.0 / 43.0), (2, 1.0 / 38.0), (3, 1.0 / 33.0), (4, 1.0 / 28.0),
+        (5, 1.0 / 23.0), (6, 1.0 / 18.0), (7, 1.0 / 13.0), (8, 1.0 / 8.0), (9, 1.0 / 6.0),
+        (10, 1.0 / 5.0), (13, 1.0 / 4.0), (16, 1.0 / 3.0), (19, 1.0 / 2.0), (29, 1.0),
+    ]);
+
+    // TGM internal gravity, in 1/256 G, by section level
+    pub const TGM: GravityCurve = GravityCurve::Table(&[
+        (0, 4.0 / 256.0), (30, 6.0 / 256.0), (35, 8.0 / 256.0), (40, 10.0 / 256.0), (50, 12.0 / 256.0),
+        (60, 16.0 / 256.0), (70, 32.0 / 256.0), (80, 48.0 / 256.0), (90, 64.0 / 256.0), (100, 80.0 / 256.0),
+        (120, 96.0 / 256.0), (140, 112.0 / 256.0), (160, 128.0 / 256.0), (170, 144.0 / 256.0), (200, 4.0 / 256.0),
+        (220, 32.0 / 256.0), (230, 64.0 / 256.0), (233, 96.0 / 256.0), (236, 128.0 / 256.0), (239, 160.0 / 256.0),
+        (243, 192.0 / 256.0), (247, 224.0 / 256.0), (251, 1.0), (300, 2.0), (330, 3.0),
+        (360, 4.0), (400, 5.0), (420, 4.0), (450, 3.0), (500, 20.0),
+    ]);
+
+    /// Returns the (first level, cells per frame) steps of the curve, empty for fixed gravity
+    pub fn table(&self) -> &'static [(u32, f32)] {
+        match self {
+            GravityCurve::Table(steps) => steps,
+            GravityCurve::Fixed(_) => &[],
+        }
+    }
+
+    /// Returns gravity at a given level, in cells per frame. Levels below the first step get its gravity
+    pub fn at(&self, level: u32) -> f32 {
+        match self {
+            GravityCurve::Table(steps) => steps.iter().rev().find(|step| step.0 <= level).unwrap_or(&steps[0]).1,
+            GravityCurve::Fixed(gravity) => *gravity,
+        }
+    }
+}
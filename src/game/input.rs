@@ -0,0 +1,132 @@
+//! Held keys and game-controlled autorepeat (DAS/ARR), so horizontal movement
+//! does not depend on the key repeat of the operating system.
+
+use super::tetromino::Rotation;
+
+/// Keys held down during a tick
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct Input {
+    pub left: bool,
+    pub right: bool,
+    pub soft_drop: bool,
+    pub hold: bool,
+    pub rotate: Option<Rotation>,   // the rotation key pressed last
+}
+
+/// Handling settings, all times are in ticks
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Handling {
+    pub das: u32,         // delay before a held key starts repeating
+    pub arr: u32,         // delay between repeats, 0 -- jump straight to the wall
+    pub das_cut: u32,     // pause of the autorepeat after a rotation
+    pub soft_drop: f32,   // how many times faster a piece falls while soft dropping
+}
+
+/// Horizontal shift asked for by the autorepeat in a single tick
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Shift {
+    Stay,
+    Step(i32),   // one cell in a given direction
+    Wall(i32),   // as far as possible in a given direction
+}
+
+/// Autorepeat state of the horizontal keys
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct Autoshift {
+    held: Input,
+    direction: i32,
+    charge: u32,
+    cut: u32,
+}
+
+impl Autoshift {
+    /// Consumes the keys held this tick and returns the shift to apply.
+    /// The key pressed last wins when both directions are held.
+    pub fn update(&mut self, input: &Input, handling: &Handling) -> Shift {
+        let pressed_left = input.left && !self.held.left;
+        let pressed_right = input.right && !self.held.right;
+        self.held = *input;
+
+        if pressed_left || pressed_right {
+            self.direction = if pressed_left {-1} else {1};
+            self.charge = 0;
+            return Shift::Step(self.direction);
+        }
+        if (self.direction == -1 && !input.left) || (self.direction == 1 && !input.right) {
+            // fall back to the other key if it is still held
+            self.direction = if input.left {-1} else if input.right {1} else {0};
+            self.charge = 0;
+        }
+        if self.direction == 0 {
+            return Shift::Stay;
+        }
+
+        self.charge += 1;
+        if self.cut > 0 {
+            self.cut -= 1;
+            return Shift::Stay;
+        }
+        if self.charge < handling.das {
+            Shift::Stay
+        } else if handling.arr == 0 {
+            Shift::Wall(self.direction)
+        } else if (self.charge - handling.das).is_multiple_of(handling.arr) {
+            Shift::Step(self.direction)
+        } else {
+            Shift::Stay
+        }
+    }
+
+    /// Pauses the autorepeat after a rotation
+    pub fn cut(&mut self, handling: &Handling) {
+        self.cut = handling.das_cut;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HANDLING: Handling = Handling { das: 10, arr: 2, das_cut: 3, soft_drop: 20.0 };
+    const LEFT: Input = Input { left: true, right: false, soft_drop: false, hold: false, rotate: None };
+
+    /// Returns the ticks (from 0) on which holding left shifted the piece
+    fn steps(autoshift: &mut Autoshift, handling: &Handling, ticks: u32) -> Vec<u32> {
+        (0..ticks).filter(|_| autoshift.update(&LEFT, handling) != Shift::Stay).collect()
+    }
+
+    #[test]
+    fn held_key_repeats_after_das_every_arr() {
+        let mut autoshift = Autoshift::default();
+        assert_eq!(steps(&mut autoshift, &HANDLING, 16), vec![0, 10, 12, 14]);
+    }
+
+    #[test]
+    fn zero_arr_shifts_to_the_wall() {
+        let mut autoshift = Autoshift::default();
+        let handling = Handling { arr: 0, ..HANDLING };
+        assert_eq!(autoshift.update(&LEFT, &handling), Shift::Step(-1));
+        let shifts: Vec<Shift> = (1..12).map(|_| autoshift.update(&LEFT, &handling)).collect();
+        assert_eq!(shifts[8], Shift::Stay);
+        assert_eq!(shifts[9], Shift::Wall(-1));
+        assert_eq!(shifts[10], Shift::Wall(-1));
+    }
+
+    #[test]
+    fn key_pressed_last_wins() {
+        let mut autoshift = Autoshift::default();
+        autoshift.update(&LEFT, &HANDLING);
+        let both = Input { right: true, ..LEFT };
+        assert_eq!(autoshift.update(&both, &HANDLING), Shift::Step(1));
+        assert_eq!(autoshift.update(&LEFT, &HANDLING), Shift::Stay);
+    }
+
+    #[test]
+    fn das_cut_pauses_the_autorepeat() {
+        let mut autoshift = Autoshift::default();
+        assert_eq!(steps(&mut autoshift, &HANDLING, 11), vec![0, 10]);
+        autoshift.cut(&HANDLING);
+        // ticks 11 to 13 are cut, the repeat picks up again on the next even charge
+        assert_eq!(steps(&mut autoshift, &HANDLING, 6), vec![3, 5]);
+    }
+}
@@ -0,0 +1,153 @@
+use super::delay::{DelayCurve, Delays};
+use super::garbage::{GarbageRules, Holes};
+use super::gravity::{GravityCurve, Progression, MAX_TGM_LEVEL};
+use super::input::Handling;
+use super::randomizer::RandomizerKind;
+use super::rotation::RotationKind;
+use super::scoring::ScoringKind;
+use super::settings::{GameOverRules, Goal, Hold, LockReset, Modifiers, Settings};
+
+/// Game modes with their default rules
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Mode {
+    Marathon,   // guideline rules
+    Classic,    // NES rules
+    Sprint(u32),   // clear a number of lines, usually 20, 40 or 100, as fast as possible
+    Ultra(u32),    // score as much as possible in a number of minutes, usually 2 or 3
+    Dig(u32),      // clear a number of garbage lines, usually 10, 18 or 100, as fast as possible
+    Survival,      // garbage keeps rising until the stack tops out
+    Master,        // TGM: race to level 999 through 20G for the best grade
+    Zen,           // practice without gravity or game over, with infinite hold and undo
+}
+
+impl Mode {
+    /// Finds a mode by its command line name, e.g. "marathon", "sprint40" or "ultra3"
+    pub fn from_name(name: &str) -> Option<Mode> {
+        match name {
+            "marathon" => Some(Mode::Marathon),
+            "classic" => Some(Mode::Classic),
+            "survival" => Some(Mode::Survival),
+            "master" => Some(Mode::Master),
+            "zen" => Some(Mode::Zen),
+            _ => {
+                let (kind, number) = name.split_at(name.find(|c: char| c.is_ascii_digit())?);
+                let number = number.parse().ok().filter(|n| *n > 0)?;
+                match kind {
+                    "sprint" => Some(Mode::Sprint(number)),
+                    "ultra" => Some(Mode::Ultra(number)),
+                    "dig" => Some(Mode::Dig(number)),
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    pub fn settings(&self) -> Settings {
+        match self {
+            Mode::Marathon => Settings {
+                width: 10,
+                height: 20,
+                buffer: 20,
+                rotation: RotationKind::Srs,
+                randomizer: RandomizerKind::Bag7,
+                preview: 5,
+                ghost: true,
+                hold: Hold::Classic,
+                hold_rotation: false,
+                initial_hold: false,       // without ARE a key still held from the last piece would
+                initial_rotation: false,   // carry over to the next one
+                delays: DelayCurve::Fixed(Delays { are: 0, line_clear: 0, lock: 30 }),
+                lock_reset: LockReset::Step,
+                handling: Handling { das: 10, arr: 2, das_cut: 1, soft_drop: 20.0 },
+                scoring: ScoringKind::Guideline,
+                level: 1,
+                progression: Progression::Fixed(10),
+                gravity: GravityCurve::GUIDELINE,
+                game_over: GameOverRules { block_out: true, lock_out: true, top_out: true },
+                goal: Goal::Endless,
+                garbage: GarbageRules { rows: 0, holes: Holes::Random, rise: 0 },
+                modifiers: Modifiers::default(),
+                undo: 0,
+            },
+            Mode::Classic => Settings {
+                width: 10,
+                height: 20,
+                buffer: 2,
+                rotation: RotationKind::Nrs,
+                randomizer: RandomizerKind::NesReroll,
+                preview: 1,
+                ghost: false,
+                hold: Hold::Disabled,
+                hold_rotation: false,
+                initial_hold: false,
+                initial_rotation: false,
+                delays: DelayCurve::Fixed(Delays { are: 0, line_clear: 0, lock: 30 }),
+                lock_reset: LockReset::Never,
+                handling: Handling { das: 16, arr: 6, das_cut: 0, soft_drop: 10.0 },
+                scoring: ScoringKind::Nes,
+                level: 0,
+                progression: Progression::Nes,
+                gravity: GravityCurve::NES,
+                game_over: GameOverRules { block_out: true, lock_out: false, top_out: true },
+                goal: Goal::Endless,
+                garbage: GarbageRules { rows: 0, holes: Holes::Random, rise: 0 },
+                modifiers: Modifiers::default(),
+                undo: 0,
+            },
+            Mode::Sprint(lines) => Settings {
+                progression: Progression::Static,
+                goal: Goal::Lines(*lines),
+                ..Mode::Marathon.settings()
+            },
+            Mode::Ultra(minutes) => Settings {
+                progression: Progression::Static,
+                goal: Goal::Time(minutes * 60),
+                ..Mode::Marathon.settings()
+            },
+            Mode::Dig(lines) => Settings {
+                progression: Progression::Static,
+                goal: Goal::Garbage(*lines),
+                garbage: GarbageRules { rows: 10.min(*lines), holes: Holes::Messy(30), rise: 0 },
+                ..Mode::Marathon.settings()
+            },
+            Mode::Survival => Settings {
+                progression: Progression::Static,
+                garbage: GarbageRules { rows: 5, holes: Holes::Messy(30), rise: 3 * 60 },
+                ..Mode::Marathon.settings()
+            },
+            Mode::Master => Settings {
+                width: 10,
+                height: 20,
+                buffer: 2,
+                rotation: RotationKind::Ars,
+                randomizer: RandomizerKind::History,
+                preview: 1,
+                ghost: false,
+                hold: Hold::Disabled,
+                hold_rotation: false,
+                initial_hold: false,
+                initial_rotation: true,
+                delays: DelayCurve::MASTER,
+                lock_reset: LockReset::Never,
+                handling: Handling { das: 14, arr: 1, das_cut: 0, soft_drop: 64.0 },
+                scoring: ScoringKind::Tgm,
+                level: 0,
+                progression: Progression::Tgm,
+                gravity: GravityCurve::TGM,
+                game_over: GameOverRules { block_out: true, lock_out: false, top_out: true },
+                goal: Goal::Level(MAX_TGM_LEVEL),
+                garbage: GarbageRules { rows: 0, holes: Holes::Random, rise: 0 },
+                modifiers: Modifiers::default(),
+                undo: 0,
+            },
+            Mode::Zen => Settings {
+                hold: Hold::Infinite,
+                progression: Progression::Static,
+                gravity: GravityCurve::Fixed(0.0),
+                game_over: GameOverRules { block_out: false, lock_out: false, top_out: false },
+                undo: 1000,
+                ..Mode::Marathon.settings()
+            },
+        }
+    }
+}
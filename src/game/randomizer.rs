@@ -0,0 +1,192 @@
+//! Piece generators. Every generator draws its randomness from the rng it is handed,
+//! so the same rng state always produces the same sequence.
+
+use rand::{seq::SliceRandom, Rng, RngCore};
+
+use super::tetromino::Shape;
+
+pub trait Randomizer {
+    /// Returns the next piece of the sequence
+    fn next(&mut self, rng: &mut dyn RngCore) -> Shape;
+
+    /// Returns a copy of the generator in its current state
+    fn snapshot(&self) -> Box<dyn Randomizer>;
+}
+
+/// Piece generators a game can be set up with
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum RandomizerKind {
+    Bag7,        // every 7 pieces hold one of each figure
+    Bag14,       // every 14 pieces hold two of each figure
+    History,     // TGM: 4-piece history with up to 6 rolls
+    NesReroll,   // NES: one reroll when the figure repeats
+    Random,      // uniform, no memory
+}
+
+impl RandomizerKind {
+    pub fn build(&self) -> Box<dyn Randomizer> {
+        match self {
+            RandomizerKind::Bag7 => Box::new(Bag::new(1)),
+            RandomizerKind::Bag14 => Box::new(Bag::new(2)),
+            RandomizerKind::History => Box::new(History::new(6)),
+            RandomizerKind::NesReroll => Box::new(NesReroll::default()),
+            RandomizerKind::Random => Box::new(Random),
+        }
+    }
+}
+
+/// Shuffles `copies` sets of all seven figures and deals them out before refilling
+#[derive(Clone)]
+pub struct Bag {
+    copies: usize,
+    pieces: Vec<Shape>,
+}
+
+impl Bag {
+    pub fn new(copies: usize) -> Self {
+        Bag { copies, pieces: Vec::new() }
+    }
+}
+
+impl Randomizer for Bag {
+    fn next(&mut self, rng: &mut dyn RngCore) -> Shape {
+        if self.pieces.is_empty() {
+            for _ in 0..self.copies {
+                self.pieces.extend_from_slice(&Shape::ALL);
+            }
+            self.pieces.shuffle(rng);
+        }
+        self.pieces.pop().unwrap()
+    }
+
+    fn snapshot(&self) -> Box<dyn Randomizer> {
+        Box::new(self.clone())
+    }
+}
+
+/// Rolls a figure up to `rolls` times in all, rolling again while it is one of the last four dealt
+#[derive(Clone)]
+pub struct History {
+    rolls: usize,
+    history: [Shape; 4],
+    first: bool,
+}
+
+impl History {
+    pub fn new(rolls: usize) -> Self {
+        History { rolls, history: [Shape::Z, Shape::Z, Shape::S, Shape::S], first: true }
+    }
+}
+
+impl Randomizer for History {
+    fn next(&mut self, rng: &mut dyn RngCore) -> Shape {
+        let shape = if self.first {
+            // the first piece is never an S, Z or O
+            self.first = false;
+            *[Shape::I, Shape::T, Shape::L, Shape::J].choose(rng).unwrap()
+        } else {
+            let mut shape: Shape = rng.gen();
+            for _ in 1..self.rolls {
+                if !self.history.contains(&shape) {
+                    break;
+                }
+                shape = rng.gen();
+            }
+            shape
+        };
+        self.history.rotate_right(1);
+        self.history[0] = shape;
+        shape
+    }
+
+    fn snapshot(&self) -> Box<dyn Randomizer> {
+        Box::new(self.clone())
+    }
+}
+
+/// Rolls an eight-sided die where the eighth side, or a repeat of the last figure,
+/// asks for one more roll of a seven-sided die
+#[derive(Default, Clone)]
+pub struct NesReroll {
+    last: Option<Shape>,
+}
+
+impl Randomizer for NesReroll {
+    fn next(&mut self, rng: &mut dyn RngCore) -> Shape {
+        let roll = rng.gen_range(0, 8);
+        let shape = if roll == 7 || Some(Shape::ALL[roll]) == self.last {
+            Shape::ALL[rng.gen_range(0, 7)]
+        } else {
+            Shape::ALL[roll]
+        };
+        self.last = Some(shape);
+        shape
+    }
+
+    fn snapshot(&self) -> Box<dyn Randomizer> {
+        Box::new(self.clone())
+    }
+}
+
+/// Picks every figure uniformly
+pub struct Random;
+
+impl Randomizer for Random {
+    fn next(&mut self, rng: &mut dyn RngCore) -> Shape {
+        rng.gen()
+    }
+
+    fn snapshot(&self) -> Box<dyn Randomizer> {
+        Box::new(Random)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn deal(kind: RandomizerKind, seed: u64, n: usize) -> Vec<Shape> {
+        let mut randomizer = kind.build();
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..n).map(|_| randomizer.next(&mut rng)).collect()
+    }
+
+    fn count(pieces: &[Shape], shape: Shape) -> usize {
+        pieces.iter().filter(|p| **p == shape).count()
+    }
+
+    #[test]
+    fn bag7_deals_every_figure_once_per_bag() {
+        for bag in deal(RandomizerKind::Bag7, 1, 70).chunks(7) {
+            assert!(Shape::ALL.iter().all(|s| count(bag, *s) == 1), "{:?}", bag);
+        }
+    }
+
+    #[test]
+    fn bag14_deals_every_figure_twice_per_bag() {
+        for bag in deal(RandomizerKind::Bag14, 2, 140).chunks(14) {
+            assert!(Shape::ALL.iter().all(|s| count(bag, *s) == 2), "{:?}", bag);
+        }
+    }
+
+    #[test]
+    fn history_never_starts_with_s_z_or_o() {
+        for seed in 0..200 {
+            let first = deal(RandomizerKind::History, seed, 1)[0];
+            assert!(![Shape::S, Shape::Z, Shape::O].contains(&first), "seed {}: {:?}", seed, first);
+        }
+    }
+
+    #[test]
+    fn snapshot_deals_the_same_sequence() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut randomizer = RandomizerKind::History.build();
+        (0..10).for_each(|_| {randomizer.next(&mut rng);});
+        let mut copy = randomizer.snapshot();
+        let mut copy_rng = rng.clone();
+        for _ in 0..50 {
+            assert_eq!(randomizer.next(&mut rng), copy.next(&mut copy_rng));
+        }
+    }
+}
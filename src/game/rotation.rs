@@ -0,0 +1,223 @@
+//! Pluggable rotation systems. A system owns the rotation states of every figure
+//! and the wall kicks that are tried when a plain rotation is blocked.
+
+use super::tetromino::{Rotation, Shape, Tetromino};
+
+pub mod ars;
+pub mod nrs;
+pub mod srs;
+
+/// A wall kick applied by a successful rotation
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Kick {
+    pub test: usize,          // index of the kick test that succeeded, 0 -- no kick
+    pub offset: (i32, i32),   // offset in board cells, y grows downwards
+}
+
+pub trait RotationSystem {
+    ///Returns all four rotation states (0, R, 2, L) of a given figure in its 4x4 matrix
+    fn states(&self, shape: Shape) -> [[u8; 4]; 4];
+
+    /// Returns kick tests for turning a figure from one state to another, in the order they are tried.
+    /// Offsets are in board cells with y growing downwards. An empty list forbids the rotation.
+    fn kicks(&self, shape: Shape, from: usize, to: usize) -> Vec<(i32, i32)>;
+
+    /// Decides whether kicks may be tried once the plain rotation was blocked.
+    /// `blocked` holds the matrix cells of the rotated state that collided.
+    fn may_kick(&self, _shape: Shape, _blocked: &[u8]) -> bool {
+        true
+    }
+}
+
+/// Rotation systems a game can be set up with
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum RotationKind {
+    Srs,     // Super Rotation System
+    Srs180,  // SRS with 180-degree kicks
+    Ars,     // Arika/TGM-style rotation
+    Nrs,     // classic NES right-handed rotation, no kicks
+}
+
+impl RotationKind {
+    pub fn system(&self) -> &'static dyn RotationSystem {
+        match self {
+            RotationKind::Srs => &srs::Srs,
+            RotationKind::Srs180 => &srs::Srs180,
+            RotationKind::Ars => &ars::Ars,
+            RotationKind::Nrs => &nrs::Nrs,
+        }
+    }
+}
+
+/// Turns a piece using a given system. `occupied` tells whether a board cell is taken
+/// by the stack or lies outside of the walls. Returns the turned piece and the kick used.
+pub fn rotate<F>(system: &dyn RotationSystem, piece: &Tetromino, r: Rotation, occupied: F) -> Option<(Tetromino, Kick)>
+    where F: Fn((i32, i32)) -> bool
+{
+    let rotated = piece.rotated(r, system);
+    let fits = |t: &Tetromino| !t.get_tiles_pos().iter().any(|c| occupied(*c));
+    for (test, offset) in system.kicks(piece.get_shape(), piece.angle(), rotated.angle()).into_iter().enumerate() {
+        if test == 1 {
+            let blocked: Vec<u8> = rotated.matrix().iter()
+                .zip(rotated.get_tiles_pos().iter())
+                .filter(|(_, c)| occupied(**c))
+                .map(|(m, _)| *m)
+                .collect();
+            if !system.may_kick(piece.get_shape(), &blocked) {
+                return None;
+            }
+        }
+        let kicked = rotated.moved(offset.0, offset.1);
+        if fits(&kicked) {
+            return Some((kicked, Kick { test, offset }));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIDTH: i32 = 10;
+    const HEIGHT: i32 = 20;
+
+    fn piece(kind: RotationKind, shape: Shape, x: i32, y: i32) -> Tetromino {
+        let mut t = Tetromino::new(shape);
+        t.set_default_pos(4, kind.system());
+        t.moved(x, y)
+    }
+
+    /// Fills the whole board but the cells of `holes`
+    fn turn_into(kind: RotationKind, t: &Tetromino, r: Rotation, holes: &[(i32, i32)]) -> Option<(Tetromino, Kick)> {
+        let mut free: Vec<(i32, i32)> = t.get_tiles_pos().to_vec();
+        free.extend_from_slice(holes);
+        rotate(kind.system(), t, r, |c| !free.contains(&c))
+    }
+
+    fn turn_on_empty(kind: RotationKind, t: &Tetromino, r: Rotation) -> Option<(Tetromino, Kick)> {
+        rotate(kind.system(), t, r, |c| c.0 < 0 || c.0 >= WIDTH || c.1 >= HEIGHT)
+    }
+
+    fn cells(t: &Tetromino) -> Vec<(i32, i32)> {
+        let mut c = t.get_tiles_pos().to_vec();
+        c.sort();
+        c
+    }
+
+    #[test]
+    fn srs_kicks_are_tried_in_order() {
+        let kind = RotationKind::Srs;
+        let t = piece(kind, Shape::T, 4, 10);
+        for (test, offset) in [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)].iter().enumerate() {
+            let target = t.rotated(Rotation::Right, kind.system()).moved(offset.0, offset.1);
+            let holes = target.get_tiles_pos();
+            let (turned, kick) = rotate(kind.system(), &t, Rotation::Right, |c| !holes.contains(&c)).unwrap();
+            assert_eq!(kick, Kick { test, offset: *offset });
+            assert_eq!(cells(&turned), cells(&target));
+        }
+    }
+
+    #[test]
+    fn srs_t_spin_triple_uses_the_last_kick() {
+        // T in spawn state hanging over a TST slot, turning left into the bottom of the well
+        let kind = RotationKind::Srs;
+        let t = piece(kind, Shape::T, 1, 15);
+        let slot = [(3, 17), (3, 18), (3, 19), (2, 18)];
+        let (turned, kick) = turn_into(kind, &t, Rotation::Left, &slot).unwrap();
+        assert_eq!(kick, Kick { test: 4, offset: (1, 2) });
+        assert_eq!(turned.angle(), 3);
+        assert_eq!(cells(&turned), {let mut s = slot.to_vec(); s.sort(); s});
+    }
+
+    #[test]
+    fn srs_i_kicks_off_the_wall() {
+        let kind = RotationKind::Srs;
+        let vertical = piece(kind, Shape::I, -1, 5).rotated(Rotation::Left, kind.system());
+        assert_eq!(cells(&vertical)[0].0, 0);
+        let (turned, kick) = turn_on_empty(kind, &vertical, Rotation::Right).unwrap();
+        assert_eq!(kick, Kick { test: 1, offset: (1, 0) });
+        assert_eq!(cells(&turned), vec!((0, 6), (1, 6), (2, 6), (3, 6)));
+    }
+
+    #[test]
+    fn srs_o_never_moves() {
+        let kind = RotationKind::Srs;
+        let o = piece(kind, Shape::O, 0, 18);
+        let (turned, kick) = turn_on_empty(kind, &o, Rotation::Right).unwrap();
+        assert_eq!(kick.test, 0);
+        assert_eq!(cells(&turned), cells(&o));
+        assert!(turn_into(kind, &o, Rotation::Right, &[]).is_some());
+    }
+
+    #[test]
+    fn srs_has_no_half_turn() {
+        let t = piece(RotationKind::Srs, Shape::T, 4, 10);
+        assert!(turn_on_empty(RotationKind::Srs, &t, Rotation::Half).is_none());
+    }
+
+    #[test]
+    fn srs180_half_turn_kicks_up() {
+        let kind = RotationKind::Srs180;
+        let t = piece(kind, Shape::T, 4, 10);
+        let (turned, kick) = turn_on_empty(kind, &t, Rotation::Half).unwrap();
+        assert_eq!(kick.test, 0);
+        assert_eq!(turned.angle(), 2);
+
+        // Floor right below the spawn state leaves no room for the plain turn
+        let grounded = piece(kind, Shape::T, 4, 18);
+        let (turned, kick) = turn_on_empty(kind, &grounded, Rotation::Half).unwrap();
+        assert_eq!(kick, Kick { test: 1, offset: (0, -1) });
+        assert_eq!(cells(&turned), vec!((4, 18), (5, 18), (5, 19), (6, 18)));
+    }
+
+    #[test]
+    fn ars_kicks_right_then_left() {
+        let kind = RotationKind::Ars;
+        // Vertical T flush with the left wall, pointing right
+        let t = piece(kind, Shape::T, -1, 5).rotated(Rotation::Left, kind.system());
+        assert_eq!(cells(&t)[0].0, 0);
+        let (turned, kick) = turn_on_empty(kind, &t, Rotation::Right).unwrap();
+        assert_eq!(kick, Kick { test: 1, offset: (1, 0) });
+        assert_eq!(turned.angle(), 0);
+
+        let right = piece(kind, Shape::T, 8, 5).rotated(Rotation::Right, kind.system());
+        assert_eq!(cells(&right).iter().map(|c| c.0).max(), Some(WIDTH - 1));
+        let (_, kick) = turn_on_empty(kind, &right, Rotation::Left).unwrap();
+        assert_eq!(kick, Kick { test: 2, offset: (-1, 0) });
+    }
+
+    #[test]
+    fn ars_center_column_blocks_kicks() {
+        let kind = RotationKind::Ars;
+        let t = piece(kind, Shape::T, 4, 10).rotated(Rotation::Right, kind.system());
+        let (x, y) = t.position();
+        // A block right under the center of the box stops the turn even though a kick would fit
+        let stack = [(x + 1, y + 2)];
+        let turned = rotate(kind.system(), &t, Rotation::Left, |c| stack.contains(&c));
+        assert!(turned.is_none());
+    }
+
+    #[test]
+    fn ars_i_does_not_kick() {
+        let kind = RotationKind::Ars;
+        let i = piece(kind, Shape::I, -2, 5).rotated(Rotation::Right, kind.system());
+        assert_eq!(cells(&i)[0].0, 0);
+        assert!(turn_on_empty(kind, &i, Rotation::Right).is_none());
+    }
+
+    #[test]
+    fn nrs_is_right_handed_without_kicks() {
+        let kind = RotationKind::Nrs;
+        let z = piece(kind, Shape::Z, 4, 10);
+        let (vertical, _) = turn_on_empty(kind, &z, Rotation::Right).unwrap();
+        let (x, y) = z.position();
+        assert_eq!(cells(&vertical), vec!((x + 1, y + 1), (x + 1, y + 2), (x + 2, y), (x + 2, y + 1)));
+        let (back, _) = turn_on_empty(kind, &vertical, Rotation::Right).unwrap();
+        assert_eq!(cells(&back), cells(&z));
+
+        let t = piece(kind, Shape::T, -1, 5).rotated(Rotation::Left, kind.system());
+        assert_eq!(cells(&t)[0].0, 0);
+        assert!(turn_on_empty(kind, &t, Rotation::Right).is_none());
+    }
+}
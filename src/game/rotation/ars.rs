@@ -0,0 +1,38 @@
+use super::super::tetromino::Shape;
+use super::RotationSystem;
+
+/// Arika rotation as in TGM: figures spawn flat side down and kick one cell right, then left
+pub struct Ars;
+
+impl RotationSystem for Ars {
+    fn states(&self, shape: Shape) -> [[u8; 4]; 4] {
+        match shape {
+            Shape::I => [[4,5,6,7], [2,6,10,14], [4,5,6,7], [2,6,10,14]],
+            Shape::J => [[4,5,6,10], [1,5,8,9], [4,8,9,10], [1,2,5,9]],
+            Shape::L => [[4,5,6,8], [0,1,5,9], [6,8,9,10], [1,5,9,10]],
+            Shape::O => [[5,6,9,10], [5,6,9,10], [5,6,9,10], [5,6,9,10]],
+            Shape::S => [[5,6,8,9], [0,4,5,9], [5,6,8,9], [0,4,5,9]],
+            Shape::Z => [[4,5,9,10], [2,5,6,9], [4,5,9,10], [2,5,6,9]],
+            Shape::T => [[4,5,6,9], [1,4,5,9], [5,8,9,10], [1,5,6,9]],
+        }
+    }
+
+    fn kicks(&self, shape: Shape, from: usize, to: usize) -> Vec<(i32, i32)> {
+        if (from + 2) % 4 == to {
+            return Vec::new();
+        }
+        match shape {
+            Shape::I | Shape::O => vec!((0, 0)),
+            _ => vec!((0, 0), (1, 0), (-1, 0)),
+        }
+    }
+
+    /// Center column rule: J, L and T do not kick when the first blocked cell
+    /// in reading order lies in the middle column of the box
+    fn may_kick(&self, shape: Shape, blocked: &[u8]) -> bool {
+        match shape {
+            Shape::J | Shape::L | Shape::T => blocked.iter().min().is_none_or(|m| m % 4 != 1),
+            _ => true,
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use super::super::tetromino::Shape;
+use super::RotationSystem;
+
+/// Classic NES rotation: right-handed, no kicks at all
+pub struct Nrs;
+
+impl RotationSystem for Nrs {
+    fn states(&self, shape: Shape) -> [[u8; 4]; 4] {
+        match shape {
+            Shape::I => [[8,9,10,11], [2,6,10,14], [8,9,10,11], [2,6,10,14]],
+            Shape::J => [[4,5,6,10], [1,5,8,9], [0,4,5,6], [1,2,5,9]],
+            Shape::L => [[4,5,6,8], [0,1,5,9], [2,4,5,6], [1,5,9,10]],
+            Shape::O => [[5,6,9,10], [5,6,9,10], [5,6,9,10], [5,6,9,10]],
+            Shape::S => [[5,6,8,9], [1,5,6,10], [5,6,8,9], [1,5,6,10]],
+            Shape::Z => [[4,5,9,10], [2,5,6,9], [4,5,9,10], [2,5,6,9]],
+            Shape::T => [[4,5,6,9], [1,4,5,9], [1,4,5,6], [1,5,6,9]],
+        }
+    }
+
+    fn kicks(&self, _shape: Shape, from: usize, to: usize) -> Vec<(i32, i32)> {
+        if (from + 2) % 4 == to {
+            return Vec::new();
+        }
+        vec!((0, 0))
+    }
+}
@@ -0,0 +1,92 @@
+use super::super::tetromino::Shape;
+use super::RotationSystem;
+
+/// Super Rotation System, as in the guideline
+pub struct Srs;
+
+/// SRS extended with 180-degree turns
+pub struct Srs180;
+
+// Kick tests are written as in the guideline, with y growing upwards
+const JLSTZ_KICKS: [[(i32, i32); 5]; 8] = [
+    [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)],  // 0 -> R
+    [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],      // R -> 0
+    [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],      // R -> 2
+    [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)],  // 2 -> R
+    [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],     // 2 -> L
+    [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],   // L -> 2
+    [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],   // L -> 0
+    [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],     // 0 -> L
+];
+
+const I_KICKS: [[(i32, i32); 5]; 8] = [
+    [(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)],    // 0 -> R
+    [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)],    // R -> 0
+    [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)],    // R -> 2
+    [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)],    // 2 -> R
+    [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)],    // 2 -> L
+    [(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)],    // L -> 2
+    [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)],    // L -> 0
+    [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)],    // 0 -> L
+];
+
+const HALF_KICKS: [[(i32, i32); 6]; 4] = [
+    [(0, 0), (0, 1), (1, 1), (-1, 1), (1, 0), (-1, 0)],      // 0 -> 2
+    [(0, 0), (1, 0), (1, 2), (1, 1), (0, 2), (0, 1)],        // R -> L
+    [(0, 0), (0, -1), (-1, -1), (1, -1), (-1, 0), (1, 0)],   // 2 -> 0
+    [(0, 0), (-1, 0), (-1, 2), (-1, 1), (0, 2), (0, 1)],     // L -> R
+];
+
+fn flip(kicks: &[(i32, i32)]) -> Vec<(i32, i32)> {
+    kicks.iter().map(|k| (k.0, -k.1)).collect()
+}
+
+impl RotationSystem for Srs {
+    fn states(&self, shape: Shape) -> [[u8; 4]; 4] {
+        match shape {
+            Shape::I => [[4,5,6,7], [2,6,10,14], [8,9,10,11], [1,5,9,13]],  // 0,  1,  2,  3
+            Shape::J => [[0,4,5,6], [1,2,5,9], [4,5,6,10], [1,5,8,9]],      // 4,  5,  6,  7
+            Shape::L => [[2,4,5,6], [1,5,9,10], [4,5,6,8], [0,1,5,9]],      // 8,  9, 10, 11
+            Shape::O => [[1,2,5,6], [1,2,5,6], [1,2,5,6], [1,2,5,6]],       //12, 13, 14, 15
+            Shape::S => [[1,2,4,5], [1,5,6,10], [5,6,8,9], [0,4,5,9]],
+            Shape::Z => [[0,1,5,6], [2,5,6,9], [4,5,9,10], [1,4,5,8]],
+            Shape::T => [[1,4,5,6], [1,5,6,9], [4,5,6,9], [1,4,5,9]],
+        }
+    }
+
+    fn kicks(&self, shape: Shape, from: usize, to: usize) -> Vec<(i32, i32)> {
+        let table = match shape {
+            Shape::O => return vec!((0, 0)), // O never kicks
+            Shape::I => &I_KICKS,
+            _ => &JLSTZ_KICKS,
+        };
+        let row = match (from, to) {
+            (0, 1) => 0,
+            (1, 0) => 1,
+            (1, 2) => 2,
+            (2, 1) => 3,
+            (2, 3) => 4,
+            (3, 2) => 5,
+            (3, 0) => 6,
+            (0, 3) => 7,
+            _ => return Vec::new(),
+        };
+        flip(&table[row])
+    }
+}
+
+impl RotationSystem for Srs180 {
+    fn states(&self, shape: Shape) -> [[u8; 4]; 4] {
+        Srs.states(shape)
+    }
+
+    fn kicks(&self, shape: Shape, from: usize, to: usize) -> Vec<(i32, i32)> {
+        if (from + 2) % 4 != to {
+            return Srs.kicks(shape, from, to);
+        }
+        match shape {
+            Shape::O => vec!((0, 0)),
+            _ => flip(&HALF_KICKS[from]),
+        }
+    }
+}
@@ -0,0 +1,174 @@
+//! Interchangeable scoring rules. A rule set turns every locked piece into a list of awards,
+//! so the HUD can show where the points came from.
+
+/// Kind of spin a piece locked with
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Spin {
+    None,
+    Mini,
+    Full,
+}
+
+/// Everything a rule set needs to know about a locked piece
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Placement {
+    pub lines: u32,           // rows cleared by the piece
+    pub spin: Spin,
+    pub level: u32,           // level the piece was locked at
+    pub combo: u32,           // clearing placements in a row before this one
+    pub back_to_back: bool,   // a difficult clear that follows another one
+    pub perfect: bool,        // the board is empty after the clear
+    pub soft_rows: u32,       // rows the piece was soft dropped
+    pub hard_rows: u32,       // rows the piece was hard or sonic dropped
+}
+
+impl Placement {
+    /// Tetrises and spins that clear lines keep a back-to-back chain going
+    pub fn is_difficult(&self) -> bool {
+        self.lines >= 4 || (self.lines > 0 && self.spin != Spin::None)
+    }
+}
+
+/// Points given for a single part of a placement
+#[derive(Debug, Clone, PartialEq)]
+pub struct Award {
+    pub label: String,
+    pub points: u64,
+}
+
+impl Award {
+    fn new(label: &str, points: u64) -> Self {
+        Award { label: label.to_string(), points }
+    }
+}
+
+pub trait ScoringRules {
+    /// Points given right away for dropping a piece by a number of rows
+    fn drop(&self, rows: u32, hard: bool) -> u64;
+
+    /// Breaks the points of a locked piece down into awards
+    fn lock(&self, placement: &Placement) -> Vec<Award>;
+}
+
+/// Scoring rule sets a game can be set up with
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ScoringKind {
+    Nes,
+    Guideline,
+    Tgm,
+}
+
+impl ScoringKind {
+    pub fn rules(&self) -> &'static dyn ScoringRules {
+        match self {
+            ScoringKind::Nes => &Nes,
+            ScoringKind::Guideline => &Guideline,
+            ScoringKind::Tgm => &Tgm,
+        }
+    }
+}
+
+fn clear_name(lines: u32) -> &'static str {
+    match lines {
+        0 => "",
+        1 => "SINGLE",
+        2 => "DOUBLE",
+        3 => "TRIPLE",
+        _ => "TETRIS",
+    }
+}
+
+/// Line clears only, multiplied by the level plus one
+pub struct Nes;
+
+impl ScoringRules for Nes {
+    fn drop(&self, rows: u32, _hard: bool) -> u64 {
+        rows as u64
+    }
+
+    fn lock(&self, p: &Placement) -> Vec<Award> {
+        let base = match p.lines {
+            0 => return Vec::new(),
+            1 => 40,
+            2 => 100,
+            3 => 300,
+            _ => 1200,
+        };
+        vec!(Award::new(clear_name(p.lines), base * (p.level as u64 + 1)))
+    }
+}
+
+/// Guideline values with T-spins, back-to-back, combos and perfect clears
+pub struct Guideline;
+
+impl ScoringRules for Guideline {
+    fn drop(&self, rows: u32, hard: bool) -> u64 {
+        if hard {2 * rows as u64} else {rows as u64}
+    }
+
+    fn lock(&self, p: &Placement) -> Vec<Award> {
+        let level = p.level.max(1) as u64;
+        let mut awards = Vec::new();
+        let (label, base) = match (p.spin, p.lines) {
+            (Spin::None, 0) => ("", 0),
+            (Spin::None, 1) => ("SINGLE", 100),
+            (Spin::None, 2) => ("DOUBLE", 300),
+            (Spin::None, 3) => ("TRIPLE", 500),
+            (Spin::None, _) => ("TETRIS", 800),
+            (Spin::Mini, 0) => ("MINI T-SPIN", 100),
+            (Spin::Mini, 1) => ("MINI T-SPIN SINGLE", 200),
+            (Spin::Mini, _) => ("MINI T-SPIN DOUBLE", 400),
+            (Spin::Full, 0) => ("T-SPIN", 400),
+            (Spin::Full, 1) => ("T-SPIN SINGLE", 800),
+            (Spin::Full, 2) => ("T-SPIN DOUBLE", 1200),
+            (Spin::Full, _) => ("T-SPIN TRIPLE", 1600),
+        };
+        if base > 0 {
+            awards.push(Award::new(label, base * level));
+        }
+        if p.back_to_back && p.is_difficult() {
+            awards.push(Award::new("BACK-TO-BACK", base * level / 2));
+        }
+        if p.lines > 0 && p.combo > 0 {
+            awards.push(Award::new(&format!("COMBO x{}", p.combo), 50 * p.combo as u64 * level));
+        }
+        if p.perfect {
+            let bonus = match p.lines {
+                1 => 800,
+                2 => 1200,
+                3 => 1800,
+                _ if p.back_to_back => 3200,
+                _ => 2000,
+            };
+            awards.push(Award::new("PERFECT CLEAR", bonus * level));
+        }
+        awards
+    }
+}
+
+/// TGM-like scoring: ((level + lines) / 4 rounded up + soft + 2 * sonic) * lines * combo * bravo.
+/// The combo multiplier is 1 plus the clearing placements in a row before this one, not the TGM combo counter
+pub struct Tgm;
+
+impl ScoringRules for Tgm {
+    fn drop(&self, _rows: u32, _hard: bool) -> u64 {
+        0 // drops only count towards a clear
+    }
+
+    fn lock(&self, p: &Placement) -> Vec<Award> {
+        if p.lines == 0 {
+            return Vec::new();
+        }
+        let base = ((p.level + p.lines) as u64).div_ceil(4) + p.soft_rows as u64 + 2 * p.hard_rows as u64;
+        let combo = 1 + p.combo as u64;
+        let bravo = if p.perfect {4} else {1};
+        let mut awards = vec!(Award::new(clear_name(p.lines), base * p.lines as u64));
+        if combo > 1 {
+            awards.push(Award::new(&format!("COMBO x{}", combo), base * p.lines as u64 * (combo - 1)));
+        }
+        if p.perfect {
+            awards.push(Award::new("BRAVO", base * p.lines as u64 * combo * (bravo - 1)));
+        }
+        awards
+    }
+}
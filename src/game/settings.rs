@@ -0,0 +1,89 @@
+use super::delay::DelayCurve;
+use super::garbage::GarbageRules;
+use super::gravity::{GravityCurve, Progression};
+use super::input::Handling;
+use super::mode::Mode;
+use super::randomizer::RandomizerKind;
+use super::rotation::RotationKind;
+use super::scoring::ScoringKind;
+
+/// How moving or turning a piece that rests on the stack affects its lock delay
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum LockReset {
+    Infinite,   // every move or turn starts the delay over
+    Step,       // same, but only MAX_LOCK_RESETS times until the piece falls to a new row
+    Never,      // only falling to a new row starts the delay over
+}
+
+pub const MAX_LOCK_RESETS: u32 = 15;
+pub const MIN_WIDTH: usize = 4;
+pub const MAX_WIDTH: usize = 20;
+
+/// When the current piece may be swapped with the held one
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Hold {
+    Disabled,
+    Classic,    // once per piece
+    Infinite,   // any number of times, for practice
+}
+
+/// What finishes a game besides a game over
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Goal {
+    Endless,
+    Lines(u32),   // clearing a number of lines
+    Time(u32),    // playing for a number of seconds
+    Garbage(u32), // clearing a number of garbage lines
+    Level(u32),   // reaching a level
+}
+
+/// Challenge modifiers, any of them can be added to any mode
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct Modifiers {
+    pub invisible: bool,   // locked blocks are hidden, only the outline of the stack shows
+    pub fading: u32,       // ticks locked blocks stay visible, 0 -- they never fade
+    pub mirror: u32,       // ticks between flips of the stack, 0 -- it never flips
+    pub big: bool,         // every mino is a 2x2 block, on a board of half the width and height
+}
+
+/// Which guideline conditions end the game. A condition that is off lets the game go on
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GameOverRules {
+    pub block_out: bool,   // a new piece overlaps the stack on spawn
+    pub lock_out: bool,    // a piece locks completely above the visible field
+    pub top_out: bool,     // garbage pushes blocks past the buffer
+}
+
+/// Rules a game is played with
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Settings {
+    pub width: usize,              // board columns, MIN_WIDTH to MAX_WIDTH
+    pub height: usize,             // visible board rows
+    pub buffer: usize,             // hidden rows above the visible field where pieces spawn
+    pub rotation: RotationKind,
+    pub randomizer: RandomizerKind,
+    pub preview: usize,            // number of upcoming pieces shown, 1 to 7
+    pub ghost: bool,               // show where the current piece lands
+    pub hold: Hold,
+    pub hold_rotation: bool,       // the held piece comes back turned the way it was held
+    pub initial_hold: bool,        // IHS, a held hold key swaps a piece as it spawns, meant for modes with ARE
+    pub initial_rotation: bool,    // IRS, a held rotation key turns a piece as it spawns, meant for modes with ARE
+    pub delays: DelayCurve,        // ARE, line clear and lock delays
+    pub lock_reset: LockReset,
+    pub handling: Handling,
+    pub scoring: ScoringKind,
+    pub level: u32,                // level the game starts at
+    pub progression: Progression,
+    pub gravity: GravityCurve,
+    pub game_over: GameOverRules,
+    pub goal: Goal,
+    pub garbage: GarbageRules,
+    pub modifiers: Modifiers,
+    pub undo: usize,               // placements that can be taken back, 0 -- no undo
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Mode::Marathon.settings()
+    }
+}
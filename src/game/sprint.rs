@@ -0,0 +1,151 @@
+//! Split times of sprint games and personal bests to race against. Times are in milliseconds.
+
+use super::field::Field;
+
+/// Lines between two splits
+pub const SPLIT_LINES: u32 = 10;
+
+/// A finished sprint
+#[derive(Debug, Clone, PartialEq)]
+pub struct Run {
+    pub goal: u32,           // lines to clear
+    pub time: u64,
+    pub splits: Vec<u64>,    // time at every SPLIT_LINES lines
+}
+
+impl Run {
+    /// Reads a run written by `to_line`, e.g. "40 61250 15000 31000 46500"
+    pub fn parse(line: &str) -> Option<Run> {
+        let mut numbers = line.split_whitespace().map(|n| n.parse::<u64>());
+        let goal = numbers.next()?.ok()? as u32;
+        let time = numbers.next()?.ok()?;
+        let splits = numbers.collect::<Result<Vec<u64>, _>>().ok()?;
+        Some(Run { goal, time, splits })
+    }
+
+    pub fn to_line(&self) -> String {
+        let mut line = format!("{} {}", self.goal, self.time);
+        self.splits.iter().for_each(|s| line += &format!(" {}", s));
+        line
+    }
+
+    /// Checks whether this run beats another one with the same goal
+    pub fn beats(&self, other: &Run) -> bool {
+        self.goal == other.goal && self.time < other.time
+    }
+}
+
+/// Records split times while a sprint is played
+pub struct Splits {
+    goal: u32,
+    splits: Vec<u64>,
+}
+
+impl Splits {
+    pub fn new(goal: u32) -> Self {
+        Splits { goal, splits: Vec::new() }
+    }
+
+    /// Records a split for every SPLIT_LINES lines cleared since the last call.
+    /// Returns true if a split was recorded
+    pub fn update(&mut self, field: &Field) -> bool {
+        let reached = (field.lines.min(self.goal) / SPLIT_LINES) as usize;
+        let recorded = self.splits.len() < reached;
+        while self.splits.len() < reached {
+            self.splits.push(millis(field));
+        }
+        recorded
+    }
+
+    pub fn splits(&self) -> &[u64] {
+        &self.splits
+    }
+
+    /// Returns the run of a finished game
+    pub fn finish(&self, field: &Field) -> Run {
+        Run { goal: self.goal, time: millis(field), splits: self.splits.clone() }
+    }
+
+    /// Returns how far ahead (negative) or behind (positive) of the best run the game is at `now`.
+    /// That is the delta at the last split, or the time past the best's next split once it is exceeded
+    pub fn delta(&self, best: &Run, now: u64) -> Option<i64> {
+        let done = self.splits.len();
+        let last = match done {
+            0 => None,
+            _ => best.splits.get(done - 1).map(|b| self.splits[done - 1] as i64 - *b as i64),
+        };
+        let next = best.splits.get(done).map(|b| now as i64 - *b as i64).filter(|d| *d > 0);
+        match (last, next) {
+            (Some(last), Some(next)) => Some(last.max(next)),
+            (last, next) => last.or(next),
+        }
+    }
+}
+
+/// Returns the game time in milliseconds
+pub fn millis(field: &Field) -> u64 {
+    field.elapsed().as_millis() as u64
+}
+
+/// Formats milliseconds as "m:ss.mmm"
+pub fn format_time(ms: u64) -> String {
+    format!("{}:{:02}.{:03}", ms / 60_000, ms / 1000 % 60, ms % 1000)
+}
+
+/// Formats a delta as "+s.mmm" or "-s.mmm"
+pub fn format_delta(delta: i64) -> String {
+    let sign = if delta < 0 {'-'} else {'+'};
+    let ms = delta.unsigned_abs();
+    format!("{}{}.{:03}", sign, ms / 1000, ms % 1000)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn best() -> Run {
+        Run { goal: 40, time: 61250, splits: vec![15000, 31000, 46500, 61250] }
+    }
+
+    #[test]
+    fn run_round_trips_through_a_line() {
+        let run = best();
+        assert_eq!(run.to_line(), "40 61250 15000 31000 46500 61250");
+        assert_eq!(Run::parse(&run.to_line()), Some(run));
+        assert_eq!(Run::parse("40 60000"), Some(Run { goal: 40, time: 60000, splits: vec![] }));
+        assert_eq!(Run::parse("40"), None);
+        assert_eq!(Run::parse("40 61250 15000 x"), None);
+    }
+
+    #[test]
+    fn only_faster_runs_with_the_same_goal_beat() {
+        let faster = Run { time: 60000, ..best() };
+        assert!(faster.beats(&best()));
+        assert!(!best().beats(&best()));
+        assert!(!Run { goal: 20, ..faster }.beats(&best()));
+    }
+
+    #[test]
+    fn delta_follows_the_last_split() {
+        let splits = Splits { goal: 40, splits: vec![14000, 32000] };
+        assert_eq!(splits.delta(&best(), 33000), Some(1000));
+        // once past the best's next split, the delta grows with the time
+        assert_eq!(splits.delta(&best(), 48000), Some(1500));
+        let ahead = Splits { goal: 40, splits: vec![14000] };
+        assert_eq!(ahead.delta(&best(), 20000), Some(-1000));
+    }
+
+    #[test]
+    fn delta_before_the_first_split() {
+        let splits = Splits::new(40);
+        assert_eq!(splits.delta(&best(), 10000), None);
+        assert_eq!(splits.delta(&best(), 16000), Some(1000));
+    }
+
+    #[test]
+    fn times_are_formatted() {
+        assert_eq!(format_time(61250), "1:01.250");
+        assert_eq!(format_delta(-1500), "-1.500");
+        assert_eq!(format_delta(250), "+0.250");
+    }
+}
@@ -0,0 +1,117 @@
+use rand::{distributions::{Distribution, Standard}, Rng};
+
+use super::rotation::{srs::Srs, RotationSystem};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Shape { I, T, L, J, S, Z, O}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Rotation {Right, Left, Half}
+
+impl Shape {
+    pub const ALL: [Shape; 7] = [Shape::I, Shape::T, Shape::L, Shape::J, Shape::S, Shape::Z, Shape::O];
+
+    ///Returns a corresponding shift in texture
+    pub fn texture_offset(&self) -> u8 {
+        match self {
+            Shape::I => 5*18,
+            Shape::J => 0,
+            Shape::L => 6*18,
+            Shape::O => 4*18,
+            Shape::S => 3*18,
+            Shape::Z => 2*18,
+            Shape::T => 18,
+        }
+    }
+}
+
+impl Distribution<Shape> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Shape {
+        match rng.gen_range(0, 7) {
+            0 => Shape::I,
+            1 => Shape::T,
+            2 => Shape::L,
+            3 => Shape::J,
+            4 => Shape::S,
+            5 => Shape::Z,
+            _ => Shape::O
+        }
+    }
+}
+
+
+/// A piece placed on the board grid. Positions are in cells, with `y` growing downwards.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Tetromino {
+    shape: Shape,
+    pos_x: i32,
+    pos_y: i32,
+    m_shape: [u8; 4],
+    r_angle: usize,
+}
+
+impl Tetromino {
+    pub fn new(shape: Shape) -> Self {
+        Tetromino {
+            shape,
+            pos_x: 0,
+            pos_y: 0,
+            m_shape: Srs.states(shape)[0],
+            r_angle: 0,
+        }
+    }
+
+    pub fn get_shape(&self) -> Shape {
+        self.shape
+    }
+
+    pub fn angle(&self) -> usize {
+        self.r_angle
+    }
+
+    pub fn position(&self) -> (i32, i32) {
+        (self.pos_x, self.pos_y)
+    }
+
+    /// Returns cells of the 4x4 matrix taken by the piece in its current state
+    pub fn matrix(&self) -> [u8; 4] {
+        self.m_shape
+    }
+
+    /// Places the piece in its spawn state at the top of a board of a given width
+    pub fn set_default_pos(&mut self, width: usize, system: &dyn RotationSystem) {
+        self.pos_x = width as i32 / 2 - 2;
+        self.pos_y = 0;
+        self.r_angle = 0;
+        self.m_shape = system.states(self.shape)[0];
+    }
+
+    /// Returns a copy of the piece shifted by (dx, dy) cells
+    pub fn moved(&self, dx: i32, dy: i32) -> Self {
+        let mut t = *self;
+        t.pos_x += dx;
+        t.pos_y += dy;
+        t
+    }
+
+    /// Returns a copy of the piece turned in a given direction, without any kicks
+    pub fn rotated(&self, r: Rotation, system: &dyn RotationSystem) -> Self {
+        let mut t = *self;
+        t.r_angle = match r {
+            Rotation::Right => (self.r_angle + 1) % 4,
+            Rotation::Left => (self.r_angle + 3) % 4,
+            Rotation::Half => (self.r_angle + 2) % 4,
+        };
+        t.m_shape = system.states(self.shape)[t.r_angle];
+        t
+    }
+
+    /// Returns board cells occupied by the piece
+    pub fn get_tiles_pos(&self) -> [(i32, i32); 4] {
+        let mut tiles = [(0, 0); 4];
+        for (i, t) in self.m_shape.iter().enumerate() {
+            tiles[i] = (self.pos_x + (t % 4) as i32, self.pos_y + (t / 4) as i32);
+        }
+        tiles
+    }
+}
@@ -0,0 +1,75 @@
+//! T-spin recognition with the 3-corner rule.
+
+use super::rotation::Kick;
+use super::scoring::Spin;
+use super::tetromino::{Rotation, Shape, Tetromino};
+
+/// Classifies a locked piece. `last_turn` is the direction and the kick of the last successful
+/// action if it was a rotation, `occupied` tells whether a cell is taken by the stack or the walls.
+pub fn detect<F>(piece: &Tetromino, last_turn: Option<(Rotation, Kick)>, occupied: F) -> Spin
+    where F: Fn((i32, i32)) -> bool
+{
+    let (direction, kick) = match last_turn {
+        Some(turn) if piece.get_shape() == Shape::T => turn,
+        _ => return Spin::None,
+    };
+    let tiles = piece.get_tiles_pos();
+    let neighbours = |c: &(i32, i32)| tiles.iter().filter(|t| (t.0 - c.0).abs() + (t.1 - c.1).abs() == 1).count();
+    let center = match tiles.iter().find(|c| neighbours(c) == 3) {
+        Some(c) => *c,
+        None => return Spin::None,
+    };
+    // the T points away from the side of the center that has no tile
+    let back = [(0, 1), (0, -1), (1, 0), (-1, 0)].iter()
+        .find(|d| !tiles.contains(&(center.0 + d.0, center.1 + d.1)))
+        .copied()
+        .unwrap();
+    let corners = [(-1, -1), (1, -1), (-1, 1), (1, 1)];
+    let taken = |d: &&(i32, i32)| occupied((center.0 + d.0, center.1 + d.1));
+    // front corners lie on the other side from the back
+    let front = corners.iter().filter(|d| d.0 * back.0 + d.1 * back.1 < 0).filter(taken).count();
+    let total = corners.iter().filter(taken).count();
+    // a quarter turn kicked one column and two rows (the SRS "TST" kick) makes a full T-spin whatever the corners
+    let tst = direction != Rotation::Half && kick.offset.0.abs() == 1 && kick.offset.1.abs() == 2;
+
+    if total < 3 {
+        Spin::None
+    } else if front == 2 || tst {
+        Spin::Full
+    } else {
+        Spin::Mini
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::rotation::RotationKind;
+
+    /// A T pointing down with its two back corners and the front corner on its left taken
+    fn one_front_corner(last_turn: (Rotation, Kick)) -> Spin {
+        let t = Tetromino::new(Shape::T).rotated(Rotation::Half, RotationKind::Srs.system()).moved(3, 10);
+        let tiles = t.get_tiles_pos();
+        let center = *tiles.iter().find(|c| tiles.iter().filter(|t| (t.0 - c.0).abs() + (t.1 - c.1).abs() == 1).count() == 3).unwrap();
+        let taken = [(center.0 - 1, center.1 - 1), (center.0 + 1, center.1 - 1), (center.0 - 1, center.1 + 1)];
+        detect(&t, Some(last_turn), |c| taken.contains(&c))
+    }
+
+    #[test]
+    fn tst_kick_makes_a_full_t_spin() {
+        assert_eq!(one_front_corner((Rotation::Right, Kick { test: 0, offset: (0, 0) })), Spin::Mini);
+        assert_eq!(one_front_corner((Rotation::Right, Kick { test: 4, offset: (-1, 2) })), Spin::Full);
+        assert_eq!(one_front_corner((Rotation::Left, Kick { test: 4, offset: (1, -2) })), Spin::Full);
+    }
+
+    #[test]
+    fn straight_two_row_kicks_stay_mini() {
+        assert_eq!(one_front_corner((Rotation::Right, Kick { test: 3, offset: (0, -2) })), Spin::Mini);
+        assert_eq!(one_front_corner((Rotation::Left, Kick { test: 3, offset: (0, 2) })), Spin::Mini);
+    }
+
+    #[test]
+    fn half_turn_kicks_stay_mini() {
+        assert_eq!(one_front_corner((Rotation::Half, Kick { test: 4, offset: (1, 2) })), Spin::Mini);
+    }
+}
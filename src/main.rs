@@ -1,11 +1,63 @@
 use sdl2::pixels::Color;
 
 use runtime_logic::menu;
+use rust_tetris::game::{field::FRAME_RATE, garbage::Holes, mode::Mode};
 
 mod engine;
 mod runtime_logic;
 
+/// Reads `--seed N`, `--mode NAME`, `--holes HOLES`, `--width N`, `--height N`, `--vsync` and the modifiers
+/// `--invisible`, `--fading SECS`, `--mirror SECS` and `--big` from the command line
+fn parse_args() -> Result<menu::Options, String> {
+    let mut options = menu::Options::default();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => {
+                let value = args.next().ok_or("--seed expects a number")?;
+                options.seed = Some(value.parse().map_err(|e| format!("bad seed {}: {}", value, e))?);
+            }
+            "--mode" => {
+                let value = args.next().ok_or("--mode expects a name")?;
+                options.mode = Some(Mode::from_name(&value).ok_or(format!("unknown mode {}", value))?);
+            }
+            "--holes" => {
+                let value = args.next().ok_or("--holes expects random, clean or a messiness percent")?;
+                options.holes = Some(match value.as_str() {
+                    "random" => Holes::Random,
+                    "clean" => Holes::Clean,
+                    _ => Holes::Messy(value.parse().map_err(|e| format!("bad holes {}: {}", value, e))?),
+                });
+            }
+            "--width" => {
+                let value = args.next().ok_or("--width expects a number")?;
+                options.width = Some(value.parse().map_err(|e| format!("bad width {}: {}", value, e))?);
+            }
+            "--height" => {
+                let value = args.next().ok_or("--height expects a number")?;
+                options.height = Some(value.parse().map_err(|e| format!("bad height {}: {}", value, e))?);
+            }
+            "--vsync" => options.vsync = true,
+            "--invisible" => options.modifiers.invisible = true,
+            "--big" => options.modifiers.big = true,
+            "--fading" => {
+                let value = args.next().ok_or("--fading expects seconds")?;
+                let secs: u32 = value.parse().map_err(|e| format!("bad fading {}: {}", value, e))?;
+                options.modifiers.fading = secs.max(1) * FRAME_RATE;
+            }
+            "--mirror" => {
+                let value = args.next().ok_or("--mirror expects seconds")?;
+                let secs: u32 = value.parse().map_err(|e| format!("bad mirror {}: {}", value, e))?;
+                options.modifiers.mirror = secs.max(1) * FRAME_RATE;
+            }
+            _ => return Err(format!("unknown argument {}", arg)),
+        }
+    }
+    Ok(options)
+}
+
 fn main() -> Result<(), String> {
-    menu::run();
+    let options = parse_args()?;
+    menu::run(options)?;
     Ok(())
-}
\ No newline at end of file
+}
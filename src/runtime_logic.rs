@@ -1,4 +1,5 @@
 pub mod menu;
+pub mod records;
 pub mod tetris;
 pub mod tetromino;
 pub mod field;
\ No newline at end of file
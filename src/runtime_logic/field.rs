@@ -1,162 +1,58 @@
-use std::collections::{HashMap, HashSet};
-use std::path::Path;
-use std::time::Instant;
+use rust_tetris::game::field::Field;
+use sdl2::pixels::Color;
 
 use crate::engine::render::Window;
 
-use super::tetromino::{draw_fn, Rotation, Tetromino};
+use super::tetromino::{self, draw_fn};
 
-//TODO implement multiple scoring rules
+const OUTLINE: Color = Color::RGBA(255, 255, 255, 255);
 
-pub struct Field {
-    pub tiles: HashMap<(u32,u32), u32>,
-    pocket: Option<Tetromino>,
-    current: Tetromino,
-    next: Tetromino,
-    cursor: usize,
-    width: usize,
-    height: usize,
-    pub pocketed: bool,
-    pub level: u8,
-    pub score: u16,
-    pub time: Instant
-}
-
-impl Field {
-    /// Creates a new instance
-    pub fn new(width: usize, height: usize) -> Self {
-        let mut current = Tetromino::new(rand::random());
-        let mut next = Tetromino::new(rand::random());
-        current.set_default_pos();
-        next.set_for_next();
-        Field {
-            tiles: HashMap::new(),
-            current,
-            next,
-            width,
-            height,
-            pocket: None,
-            pocketed: false,
-            cursor: 0,
-            level: 1,
-            score: 0,
-            time: Instant::now()
-        }
+/// Draws the stack, the current piece with its ghost, the next queue and the pocket.
+/// `origin` is the pixel of the top left visible board cell, the queue is drawn in a column starting at `queue_pos`.
+/// Board cells are `t_size` times the scale of the field, previews are always drawn with `t_size` tiles.
+/// Nothing is drawn in the hidden buffer above the visible field.
+pub fn draw(window: &mut Window, field: &Field, origin: (i32, i32), queue_pos: (i32, i32), pocket_pos: (i32, i32), t_size: u32) -> Result<(), String> {
+    let c_size = t_size * field.scale() as u32;
+    for (cell, block) in field.tiles.iter().filter(|(cell, _)| cell.1 >= 0 && field.shown(**cell)) {
+        let pos = (origin.0 + cell.0 * c_size as i32, origin.1 + cell.1 * c_size as i32);
+        draw_fn(window, pos, block.texture_offset() as u32, c_size)?;
     }
-
-    /// Modifies the score where n is a number of rows deleted
-    pub fn inc_score(&mut self, n: u16) {
-        self.score = match n {
-            1 => 40*(self.level + 1) as u16,
-            2 => 100*(self.level + 1)as u16,
-            3 => 300*(self.level + 1)as u16,
-            _ => 1200*(self.level + 1)as u16,
-        };
+    if field.outlined() {
+        draw_outline(window, field, origin, c_size)?;
     }
-
-    /// Changes level and returns new G_AMPLIFIER value
-    pub fn next_lvl(&mut self) -> f32 {
-        self.level += 1;
-        self.get_amplifier()
-    }
-
-    /// Checks if any tile Y-value is low enough to consider it a game_over
-    pub fn game_over(&mut self) -> bool {
-        self.current.get_tiles_pos().iter().any(|c| c.1 <= 1) // ANY TILE.Y IS TOO HIGH ON THE STACK
-    }
-
-    // fn lower_pieces(&mut self, n: u16, lb: u32, rb: u32, f: u32) {
-    //     self.pieces.iter_mut().for_each(|p| p.make_move(n as i32, 1, 1, lb, rb, f));
-    // }
-
-    pub fn rotate(&mut self, direction: Rotation, lb: u32, rb: u32, f: u32) {
-        self.current.rotate(direction, &self.tiles, lb, rb, f);
-    }
-
-    pub fn check_lines(&mut self, n: u16, lb: u32, rb: u32, f: u32) {
-        let pos = self.current.get_tiles_pos();
-        let mut to_remove = Vec::new();
-        for tile in pos.iter() {
-            if self.tiles.iter().map(|t| *t.1 == tile.1).count() == self.width {
-                let present = to_remove.contains(&tile.1);
-                if present {
-                    to_remove.push(tile.1);
-                }
-            }
-        }
-
-        to_remove.iter().for_each(|e| self.tiles.retain(|t, _| t.1 != *e)); // remove from hash set
-        // self.lower_pieces(n: u16, lb: u32, rb: u32, f: u32)
+    let q_size = t_size * 3 / 4;
+    for (i, shape) in field.queue().iter().enumerate() {
+        let pos = (queue_pos.0, queue_pos.1 + (i as u32 * 3 * q_size) as i32);
+        tetromino::draw_preview(window, *shape, field.rotation_system(), pos, q_size)?;
     }
-
-    /// Returns new G_AMPLIFIER value
-    pub fn get_amplifier(&self) -> f32 {
-        match self.level {
-            1 => 1.0,  // G = 50 ticks
-            2 => 0.9,  // G = 45 ticks
-            3 => 0.8,  // G = 40 ticks
-            4 => 0.7,  // G = 35 ticks
-            5 => 0.6,  // G = 30 ticks
-            6 => 0.5,  // G = 25 ticks
-            7 => 0.4,  // G = 20 ticks
-            8 => 0.3,  // G = 15 ticks
-            9 => 0.2,  // G = 10 ticks
-            _ => 0.1,  // G =  5 ticks
-        }
+    if let Some(ghost) = field.ghost() {
+        tetromino::draw_ghost(window, &ghost, origin, c_size)?;
     }
-
-    /// Returns a ref to the current piece
-    pub fn current_piece(&mut self) -> &mut Tetromino {
-        &mut self.current
+    if let Some(piece) = field.current_piece() {
+        tetromino::draw(window, piece, origin, c_size)?;
     }
-
-    /// Handles spawning of the new piece
-    pub fn next_piece(&mut self) {
-        let shape = self.current.get_shape();
-        self.current.get_tiles_pos().iter().for_each(|c| {self.tiles.insert(*c, shape.texture_offset() as u32);});
-        self.current = self.next;
-        self.next = Tetromino::new(rand::random());
-        self.cursor += 1;
-        self.current.set_default_pos();
-        self.next.set_for_next();
+    if let Some(t) = field.pocketed_piece() {
+        tetromino::draw_preview(window, t.get_shape(), field.rotation_system(), pocket_pos, t_size)?;
     }
+    Ok(())
+}
 
-    /// Handles the logic of pocketing a piece
-    pub fn pocket(&mut self) {
-        if !self.pocketed {
-            if self.pocket.is_some() {
-                let mut pocket = self.pocket.unwrap();
-                pocket.set_default_pos();
-                self.current.set_to_pocket();
-                self.pocket = Some(self.current);
-                self.current = pocket;
-            } else {
-                self.current.set_to_pocket();
-                self.pocket = Some(self.current);
-                self.current = self.next;
-                self.current.set_default_pos();
-                self.next = Tetromino::new(rand::random());
-                self.next.set_for_next();
+/// Draws the edges between the stack and empty cells
+fn draw_outline(window: &mut Window, field: &Field, origin: (i32, i32), c_size: u32) -> Result<(), String> {
+    let size = c_size as i32;
+    for cell in field.tiles.keys().filter(|cell| cell.1 >= 0) {
+        let (left, top) = (origin.0 + cell.0 * size, origin.1 + cell.1 * size);
+        let (right, bottom) = (left + size - 1, top + size - 1);
+        let edges = [((-1, 0), (left, top), (left, bottom)),
+                     ((1, 0), (right, top), (right, bottom)),
+                     ((0, -1), (left, top), (right, top)),
+                     ((0, 1), (left, bottom), (right, bottom))];
+        for (side, start, finish) in edges.iter() {
+            let neighbour = (cell.0 + side.0, cell.1 + side.1);
+            if !field.occupied(neighbour) {
+                window.draw_line(OUTLINE, *start, *finish)?;
             }
-            self.pocketed = true;
         }
     }
-
-    /// Draws pieces on the screen
-    pub fn draw(&mut self, window: &mut Window) {
-        self.tiles.iter().for_each(|kv| {
-            draw_fn(window, *kv.0, *kv.1, 18);
-        });
-        self.next.draw(window).unwrap();
-        self.current.draw(window).unwrap();
-        match self.pocket {
-            Some(t) => t.draw(window).unwrap(),
-            None => ()
-        }
-    }
-
-    /// check pieces for collision
-    pub fn has_collision(&mut self) -> bool {
-        self.current_piece().get_tiles_pos().iter().any(|c| self.tiles.contains_key(c))
-    }
-}
\ No newline at end of file
+    Ok(())
+}
@@ -5,6 +5,8 @@ use sdl2::keyboard::Keycode;
 use sdl2::pixels::Color;
 use sdl2::rect::Rect;
 
+use rust_tetris::game::{garbage::Holes, mode::Mode, settings::Modifiers};
+
 use crate::engine::{render, text::Text};
 
 use super::tetris;
@@ -27,8 +29,21 @@ pub fn save_name(text: &str, pos_x: u32, pos_y: u32) {
 }
 
 
-pub fn run() -> Result<(), String>{
-    let mut window = render::Window::new(320, 480);
+/// Command line options
+#[derive(Debug, Default)]
+pub struct Options {
+    pub seed: Option<u64>,   // seed of the game, a random one when not given
+    pub mode: Option<Mode>,  // mode of the game, marathon when not given
+    pub holes: Option<Holes>,    // where garbage holes go instead of the mode's
+    pub modifiers: Modifiers,    // challenge modifiers added to the mode
+    pub vsync: bool,         // wait for the display refresh instead of limiting the frame rate
+    pub width: Option<usize>,    // board columns instead of the mode's
+    pub height: Option<usize>,   // visible board rows instead of the mode's
+}
+
+/// Shows the main menu
+pub fn run(options: Options) -> Result<(), String>{
+    let mut window = render::Window::new(320, 480, options.vsync);
     let mut menu_text = vec!(
                             Text::new("Rust.Tetris!", 10, 20, 100, None),
                             Text::new("New game", 70, 150, 40, None),
@@ -80,8 +95,13 @@ pub fn run() -> Result<(), String>{
         ::std::thread::sleep(Duration::new(0, 1_000_000_000u32 / 60));
     }
 
+    let mut settings = options.mode.unwrap_or(Mode::Marathon).settings();
+    settings.width = options.width.unwrap_or(settings.width);
+    settings.height = options.height.unwrap_or(settings.height);
+    settings.garbage.holes = options.holes.unwrap_or(settings.garbage.holes);
+    settings.modifiers = options.modifiers;
     match cursor {
-        1 => tetris::run(&mut window, &mut event_pump)?,
+        1 => tetris::run(&mut window, &mut event_pump, settings, options.seed.unwrap_or_else(rand::random))?,
         _ => (),
     }
 
@@ -0,0 +1,30 @@
+//! Personal bests, kept in a text file next to the game data
+
+use std::fs;
+
+use rust_tetris::game::sprint::Run;
+
+const RECORDS: &str = "data/records.txt";
+
+/// Returns the best saved sprint for a line goal
+pub fn best_sprint(goal: u32) -> Option<Run> {
+    let text = fs::read_to_string(RECORDS).ok()?;
+    text.lines().filter_map(sprint_run).find(|r| r.goal == goal)
+}
+
+/// Saves a sprint with its splits if it beats the best one for its goal. Returns true if it was saved
+pub fn save_sprint(run: &Run) -> Result<bool, String> {
+    if best_sprint(run.goal).is_some_and(|best| !run.beats(&best)) {
+        return Ok(false);
+    }
+    let text = fs::read_to_string(RECORDS).unwrap_or_default();
+    let mut lines: Vec<&str> = text.lines().filter(|l| sprint_run(l).is_none_or(|r| r.goal != run.goal)).collect();
+    let line = format!("sprint {}", run.to_line());
+    lines.push(&line);
+    fs::write(RECORDS, lines.join("\n") + "\n").map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+fn sprint_run(line: &str) -> Option<Run> {
+    line.strip_prefix("sprint ").and_then(Run::parse)
+}
@@ -1,57 +1,161 @@
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use rand::{distributions::{Distribution, Standard}, Rng};
+use rust_tetris::game::{
+    field::{Field, GameOver, FRAME_RATE},
+    grade::Grading,
+    gravity::Progression,
+    input::Input,
+    scoring::{Placement, Spin},
+    settings::{Goal, Settings},
+    sprint::{self, Splits},
+    tetromino::Rotation,
+};
 use sdl2::{
     event::Event,
     keyboard::Keycode,
     pixels::Color,
-    rect::Rect,
 };
 
 use crate::engine::{render::Window, text::cast_with_capacity, text::Text};
 
-use super::field::Field;
-use super::tetromino::Rotation;
+use super::{field, records};
 
-//TODO GAMEOVER SCREEN
 //TODO JOYSTICK
 //TODO GAME SETTINGS BEFORE START
 
 // CONSTS
-pub const GRAVITY: f32 = 50.0;
 pub const WHITE: Color = Color::RGBA(255, 255,255,255);
+pub const RED: Color = Color::RGBA(255, 64, 64, 255);
 pub const H_UI: i32 = 54;
-pub const SZ_TILE: u32 = 18;
-pub const W_FIELD: usize = 10;
-pub const H_FIELD: usize = 20;
+pub const SZ_TILE: u32 = 18;           // largest tile size, boards that do not fit get smaller tiles
+pub const POPUP_TICKS: u32 = 90;
+pub const WARNING_SECS: u64 = 10;      // a time limit is announced this long before it runs out
+pub const MAX_FPS: u32 = 120;          // frame limiter when vsync is off
+pub const MAX_CATCH_UP: u32 = 15;      // ticks simulated at most per frame after a stall
 
 
-pub fn run(window: &mut Window, event_pump: &mut sdl2::EventPump) -> Result<(), String> {
-    let mut field = Field::new(W_FIELD, H_FIELD);
-    let mut ticks = 0;
-    let mut lines = 0;
-    let mut g_amplifier = 1.0; // The less it becomes -- the faster pieces will fall
-    let mut accelerated = false;
-    let mut hard_drop = false;
+/// Returns announcements for a locked piece, e.g. "T-SPIN DOUBLE" or "COMBO x2"
+fn banners(p: &Placement) -> Vec<String> {
+    let mut banners = Vec::new();
+    let lines = ["", " SINGLE", " DOUBLE", " TRIPLE"];
+    match p.spin {
+        Spin::Mini => banners.push(format!("MINI T-SPIN{}", lines[p.lines.min(3) as usize])),
+        Spin::Full => banners.push(format!("T-SPIN{}", lines[p.lines.min(3) as usize])),
+        Spin::None if p.lines >= 4 => banners.push("TETRIS".to_string()),
+        Spin::None => (),
+    }
+    if p.back_to_back {
+        banners.push("BACK-TO-BACK".to_string());
+    }
+    if p.lines > 0 && p.combo > 0 {
+        banners.push(format!("COMBO x{}", p.combo));
+    }
+    if p.perfect {
+        banners.push("PERFECT CLEAR".to_string());
+    }
+    banners
+}
 
+/// Shows how the game ended, the final score, the seed and mode results until a key is pressed
+fn game_over_screen(window: &mut Window, event_pump: &mut sdl2::EventPump, field: &Field, results: &[String]) -> Result<(), String> {
+    let title = if field.game_over() == Some(GameOver::Completed) {"FINISHED"} else {"GAME OVER"};
+    let mut text = vec!(Text::new(title, 60, 120, 40, Some(WHITE)),
+                        Text::new(&format!("Score: {}", field.score), 60, 200, 20, Some(WHITE)),
+                        Text::new(&format!("Lines: {}", field.lines), 60, 230, 20, Some(WHITE)),
+                        Text::new(&format!("Seed: {}", field.seed()), 60, 260, 20, Some(WHITE)));
+    let reason = match field.game_over() {
+        Some(GameOver::BlockOut) => Some("BLOCK OUT"),
+        Some(GameOver::LockOut) => Some("LOCK OUT"),
+        Some(GameOver::TopOut) => Some("TOP OUT"),
+        Some(GameOver::Completed) | None => None,
+    };
+    if let Some(reason) = reason {
+        text.push(Text::new(reason, 60, 165, 20, Some(WHITE)));
+    }
+    for (i, line) in results.iter().enumerate() {
+        text.push(Text::new(line, 60, 290 + 30 * i as u32, 20, Some(WHITE)));
+    }
+    loop {
+        for event in event_pump.poll_iter() {
+            match event {
+                Event::Quit { .. } |
+                Event::KeyDown { keycode: Some(Keycode::Escape), .. } |
+                Event::KeyDown { keycode: Some(Keycode::Space), repeat: false, .. } => return Ok(()),
+                _ => {}
+            }
+        }
+        window.draw_bg(Color::RGBA(0, 0, 0, 255));
+        window.draw_text(&text, 0)?;
+        window.present();
+        ::std::thread::sleep(Duration::new(0, 1_000_000_000u32 / 60));
+    }
+}
+
+pub fn run(window: &mut Window, event_pump: &mut sdl2::EventPump, settings: Settings, seed: u64) -> Result<(), String> {
+    let mut field = Field::new(settings, seed);
+    let mut input = Input::default();
+
+    let scale = field.scale() as u32;
+    let (w_field, h_field) = (field.width() as u32 * scale, field.height() as u32 * scale);
     let ui_bottom_offset = (window.height - SZ_TILE * 3 + 4) as i32;
     let border_left: u32 = SZ_TILE * 3 + 16; // 3 -- random offset
-    let border_right: u32 = border_left + W_FIELD as u32 * SZ_TILE;
+    let t_size = SZ_TILE.min((window.width - border_left * 2) / w_field)
+                        .min((ui_bottom_offset as u32 - H_UI as u32 - 16) / h_field);
+    let border_right: u32 = border_left + w_field * t_size;
+    let origin = (border_left as i32 + 2, ui_bottom_offset - (h_field * t_size) as i32);
+    let queue_pos = (border_right as i32 + 8, H_UI + 30);
+    let pocket_pos = (SZ_TILE as i32 * 13, SZ_TILE as i32 * 24);
     let mut ui = vec!(Text::new("Score:", 10, 10, 15, Some(WHITE)),
                       Text::new("000000", 55, 11, 15, Some(WHITE)),
                       Text::new("Level:", 14, 30, 15, Some(WHITE)),
                       Text::new("01", 55, 31, 15, Some(WHITE)),
-                      Text::new("NEXT:", 165, 10, 15, Some(WHITE)),
+                      Text::new("NEXT:", border_right + 8, H_UI as u32 + 8, 15, Some(WHITE)),
                       Text::new("APM:", 15, ui_bottom_offset as u32 + 10, 15, Some(WHITE)),
                       Text::new("000", 55, ui_bottom_offset as u32 + 11, 15, Some(WHITE)),
                       Text::new("Lines:", 10, ui_bottom_offset as u32 + 30, 15, Some(WHITE)),
                       Text::new("000", 55, ui_bottom_offset as u32 + 31, 15, Some(WHITE)),
                       Text::new("Time:", 128, ui_bottom_offset as u32 + 10, 15, Some(WHITE)),
-                      Text::new("000", 130, ui_bottom_offset as u32 + 31, 15, Some(WHITE)),
+                      Text::new("0:00.000", 124, ui_bottom_offset as u32 + 31, 15, Some(WHITE)),
                       Text::new("Hold:", 180, ui_bottom_offset as u32 + 10, 15, Some(WHITE)));
+    let mut popups: Vec<(Text, u32)> = Vec::new(); // award labels with the ticks they have left
+    let mut announced: Vec<(Text, u32)> = Vec::new(); // announcements in the middle of the field
+
+    let mut warned = false; // the end of a time limit was announced
+    let mut flips = 0;      // mirror flips announced
+
+    // LINES LEFT TO A GOAL, SPRINT SPLITS AGAINST THE PERSONAL BEST
+    let (goal, sprint) = match settings.goal {
+        Goal::Lines(lines) => (Some(lines), Some(lines)),
+        Goal::Garbage(lines) => (Some(lines), None),
+        _ => (None, None),
+    };
+    let mut splits = sprint.map(Splits::new);
+    let best = sprint.and_then(records::best_sprint);
+    let mut goal_ui = vec!(Text::new("Left:", 150, 10, 15, Some(WHITE)),
+                           Text::new("000", 190, 11, 15, Some(WHITE)));
+    if let Some(best) = &best {
+        goal_ui.push(Text::new(&format!("Best: {}", sprint::format_time(best.time)), 150, 30, 15, Some(WHITE)));
+        goal_ui.push(Text::new("0.000", 240, 11, 15, Some(WHITE)));
+    }
+
+    // MASTER GRADE
+    let mut grading = (settings.progression == Progression::Tgm).then(Grading::default);
+    let mut grade_ui = vec!(Text::new("Grade:", 150, 10, 15, Some(WHITE)),
+                            Text::new("9", 200, 11, 15, Some(WHITE)));
+
+    let tick = Duration::from_secs(1) / FRAME_RATE;
+    let frame_budget = Duration::from_secs(1) / MAX_FPS;
+    let mut previous = Instant::now();
+    let mut lag = Duration::from_secs(0);   // real time not simulated yet
+    let mut fps_timer = Instant::now();
+    let mut fps_frames = 0;
 
     //Todo miami mode
     'running: loop {
+        let frame_start = Instant::now();
+        lag = (lag + (frame_start - previous)).min(tick * MAX_CATCH_UP);
+        previous = frame_start;
+
         window.draw_bg(Color::RGBA(0, 0, 0, 255));
         for event in event_pump.poll_iter() {
             match event {
@@ -59,92 +163,178 @@ pub fn run(window: &mut Window, event_pump: &mut sdl2::EventPump) -> Result<(),
                 Event::KeyDown { keycode: Some(Keycode::Escape), .. } => {
                     break 'running
                 },
-                Event::KeyDown { keycode: Some(Keycode::Left), .. } => {
-                    field.current_piece().make_move(1, -1, 0, border_left, border_right, ui_bottom_offset as u32);
-                    if field.has_collision() {
-                        field.current_piece().make_move(1, 1, 0, border_left, border_right, ui_bottom_offset as u32);
-                    }
-                },
-                Event::KeyDown { keycode: Some(Keycode::Right), .. } => {
-                    field.current_piece().make_move(1, 1, 0, border_left, border_right, ui_bottom_offset as u32);
-                    if field.has_collision() {
-                        field.current_piece().make_move(1, -1, 0, border_left, border_right, ui_bottom_offset as u32);
-                    }
-                },
-                Event::KeyDown { keycode: Some(Keycode::Down), .. } => {
-                    if !accelerated {
-                        g_amplifier = (10.0 * g_amplifier) / 100.0;
-                        accelerated = true;
-                    }
-                }
-                Event::KeyUp { keycode: Some(Keycode::Down), .. } => {
-                    if accelerated {
-                        g_amplifier = (100.0 * g_amplifier) / 10.0;
-                        accelerated = false;
-                    }
+                Event::KeyDown { keycode: Some(Keycode::Left), .. } => input.left = true,
+                Event::KeyUp { keycode: Some(Keycode::Left), .. } => input.left = false,
+                Event::KeyDown { keycode: Some(Keycode::Right), .. } => input.right = true,
+                Event::KeyUp { keycode: Some(Keycode::Right), .. } => input.right = false,
+                Event::KeyDown { keycode: Some(Keycode::Down), .. } => input.soft_drop = true,
+                Event::KeyUp { keycode: Some(Keycode::Down), .. } => input.soft_drop = false,
+                Event::KeyDown { keycode: Some(Keycode::Up), repeat: false, .. } => {
+                    field.hard_drop();
                 }
-                Event::KeyUp { keycode: Some(Keycode::Up), .. } => {
-                    if !hard_drop {
-                        g_amplifier = 0.0;
-                        hard_drop = true;
-                    }
+                Event::KeyDown { keycode: Some(Keycode::Space), repeat: false, .. } => {
+                    field.sonic_drop();
                 }
-                Event::KeyDown { keycode: Some(Keycode::E), .. } => {
-                    field.rotate(Rotation::Right, border_left, border_right, ui_bottom_offset as u32);
+                Event::KeyDown { keycode: Some(Keycode::E), repeat: false, .. } => {
+                    field.rotate(Rotation::Right);
+                    input.rotate = Some(Rotation::Right);
                 },
-                Event::KeyDown { keycode: Some(Keycode::Q), .. } => {
-                    field.rotate(Rotation::Left, border_left, border_right, ui_bottom_offset as u32);
+                Event::KeyDown { keycode: Some(Keycode::Q), repeat: false, .. } => {
+                    field.rotate(Rotation::Left);
+                    input.rotate = Some(Rotation::Left);
+                }
+                Event::KeyDown { keycode: Some(Keycode::W), repeat: false, .. } => {
+                    field.rotate(Rotation::Half);
+                    input.rotate = Some(Rotation::Half);
                 }
-                Event::KeyDown { keycode: Some(Keycode::R), .. } => {
+                Event::KeyUp { keycode: Some(Keycode::E), .. } |
+                Event::KeyUp { keycode: Some(Keycode::Q), .. } |
+                Event::KeyUp { keycode: Some(Keycode::W), .. } => input.rotate = None,
+                Event::KeyDown { keycode: Some(Keycode::R), repeat: false, .. } => {
                     field.pocket();
+                    input.hold = true;
+                }
+                Event::KeyUp { keycode: Some(Keycode::R), .. } => input.hold = false,
+                Event::KeyDown { keycode: Some(Keycode::Z), repeat: false, .. } => {
+                    field.undo();
+                }
+                Event::KeyDown { keycode: Some(Keycode::Y), repeat: false, .. } => {
+                    field.redo();
                 }
                 _ => {}
             }
         }
         // DRAW UI OUTLINES
-        window.draw_line(WHITE, (0, H_UI), (window.width as i32, H_UI));
-        window.draw_line(WHITE, (145, 0), (145, H_UI));
-        window.draw_line(WHITE, (border_left as i32, ui_bottom_offset), (border_left as i32, H_UI));
-        window.draw_line(WHITE, (border_right as i32, ui_bottom_offset), (border_right as i32, H_UI));
-        window.draw_line(WHITE, (120, ui_bottom_offset), (120, window.height as i32));
-        window.draw_line(WHITE, (170, ui_bottom_offset), (170, window.height as i32));
-        window.draw_line(WHITE, (0, ui_bottom_offset), (window.width as i32, ui_bottom_offset));
+        window.draw_line(WHITE, (0, H_UI), (window.width as i32, H_UI))?;
+        window.draw_line(WHITE, (145, 0), (145, H_UI))?;
+        window.draw_line(WHITE, (border_left as i32, ui_bottom_offset), (border_left as i32, H_UI))?;
+        window.draw_line(WHITE, (border_right as i32, ui_bottom_offset), (border_right as i32, H_UI))?;
+        window.draw_line(WHITE, (120, ui_bottom_offset), (120, window.height as i32))?;
+        window.draw_line(WHITE, (170, ui_bottom_offset), (170, window.height as i32))?;
+        window.draw_line(WHITE, (0, ui_bottom_offset), (window.width as i32, ui_bottom_offset))?;
 
         window.draw_text(&ui, 0)?; // DRAW USER INTERFACE
-        field.draw(window);        // DRAW PIECES
+        if goal.is_some() {
+            window.draw_text(&goal_ui, 0)?;
+        }
+        if grading.is_some() {
+            window.draw_text(&grade_ui, 0)?;
+        }
+        field::draw(window, &field, origin, queue_pos, pocket_pos, t_size)?; // DRAW PIECES
+        for (i, popup) in popups.iter_mut().enumerate() {
+            popup.0.pos_y = (H_UI + 10 + 18 * i as i32) as u32;
+        }
+        window.draw_text(&popups.iter().map(|p| p.0.clone()).collect(), 0)?; // DRAW AWARDS
+        for (i, banner) in announced.iter_mut().enumerate() {
+            banner.0.pos_y = (origin.1 + (h_field * t_size / 2) as i32 + 24 * i as i32) as u32;
+        }
+        window.draw_text(&announced.iter().map(|b| b.0.clone()).collect(), 0)?; // DRAW ANNOUNCEMENTS
 
         window.present();          // PRESENT BUFFER TO THE SCREEN
-        // ::std::thread::sleep(std::time::Duration::new(0, 1_000_000_000u32 / 500)); // 1 tick
-
-        // ON PIECE UPDATE
-        if ticks >= (GRAVITY * g_amplifier) as u32 {
-            ui[10].change_text(&cast_with_capacity(field.time.elapsed().as_secs() as u16, 3)); // UPDATE TIME
-            field.current_piece().make_move(1, 1, 1, border_left, border_right, ui_bottom_offset as u32);
-            if field.has_collision() {
-                field.current_piece().make_move(1, -1, 1, border_left, border_right, ui_bottom_offset as u32);
-                field.current_piece().deactivate();
-            }
-            if !field.current_piece().is_active() {
-                // field.check_lines(1, border_left, border_right, ui_bottom_offset as u32);
-                if field.game_over() {break 'running;}
 
-                ui[1].change_text(&cast_with_capacity(field.score, 6)); // UPDATE SCORE
-                ui[3].change_text(&cast_with_capacity(field.level as u16, 2)); // UPDATE LEVEL
+        // FIXED TIMESTEP SIMULATION
+        // splits are taken on the tick of the lock: drops made by keys before the first tick, the rest after each tick
+        let mut new_split = splits.as_mut().is_some_and(|s| s.update(&field));
+        while lag >= tick {
+            lag -= tick;
+            field.tick(&input);
+            new_split |= splits.as_mut().is_some_and(|s| s.update(&field));
+            popups.iter_mut().chain(announced.iter_mut()).for_each(|p| p.1 = p.1.saturating_sub(1));
+        }
+        if let Some(splits) = splits.as_ref() {
+            if new_split {
+                let split = splits.splits().last().copied().unwrap_or(0);
+                let mut label = format!("SPLIT {}", sprint::format_time(split));
+                if let Some(best) = &best {
+                    label += &format!(" {}", sprint::format_delta(splits.delta(best, split).unwrap_or(0)));
+                }
+                popups.push((Text::new(&label, border_left + 8, 0, 14, Some(WHITE)), POPUP_TICKS));
+            }
+        }
+        if let Some(grading) = grading.as_mut() {
+            grading.update(&field);
+            grade_ui[1].change_text(grading.grade(&field)); // UPDATE GRADE
+        }
+        if field.is_over() {
+            let mut results = Vec::new();
+            if let Some(grading) = &grading {
+                results.push(format!("Grade: {}", grading.grade(&field)));
+                results.push(format!("Time: {}", sprint::format_time(sprint::millis(&field))));
+            }
+            if let (Goal::Time(_), Some(GameOver::Completed)) = (settings.goal, field.game_over()) {
+                results.push(format!("PPS: {:.2}", field.pieces_per_second()));
+            }
+            if let (Goal::Garbage(_), Some(GameOver::Completed)) = (settings.goal, field.game_over()) {
+                results.push(format!("Time: {}", sprint::format_time(sprint::millis(&field))));
+            }
+            if let (Some(splits), Some(GameOver::Completed)) = (&splits, field.game_over()) {
+                let run = splits.finish(&field);
+                results.push(format!("Time: {}", sprint::format_time(run.time)));
+                match records::save_sprint(&run) {
+                    Ok(true) => results.push("NEW PERSONAL BEST".to_string()),
+                    Ok(false) => if let Some(best) = &best {
+                        results.push(format!("Best: {}", sprint::format_time(best.time)));
+                    },
+                    Err(e) => results.push(format!("Not saved: {}", e)),
+                }
+            }
+            game_over_screen(window, event_pump, &field, &results)?;
+            break 'running;
+        }
 
-                window.set_title(&format!("NAME_HERE; Lines: {}; FPS: {}", lines, 11));
+        for award in field.take_awards() {
+            let label = format!("{} +{}", award.label, award.points);
+            popups.push((Text::new(&label, border_left + 8, 0, 14, Some(WHITE)), POPUP_TICKS));
+        }
+        for placement in field.take_placements() {
+            for label in banners(&placement) {
+                announced.push((Text::new(&label, border_left + 12, 0, 20, Some(WHITE)), POPUP_TICKS));
+            }
+        }
+        if field.flips() > flips {
+            flips = field.flips();
+            announced.push((Text::new("MIRROR", border_left + 12, 0, 20, Some(WHITE)), POPUP_TICKS));
+        }
+        popups.retain(|p| p.1 > 0);
+        announced.retain(|b| b.1 > 0);
 
-                field.pocketed = false;
-                field.next_piece();
-                if hard_drop {
-                    hard_drop = false;
-                    g_amplifier = field.get_amplifier();
+        ui[1].change_text(&cast_with_capacity(field.score, 6)); // UPDATE SCORE
+        ui[3].change_text(&cast_with_capacity(field.level as u64, 2)); // UPDATE LEVEL
+        ui[8].change_text(&cast_with_capacity(field.lines as u64, 3)); // UPDATE LINES
+        match field.remaining() {
+            Some(left) => {
+                ui[10].change_text(&sprint::format_time(left.as_millis() as u64)); // UPDATE COUNTDOWN
+                if left.as_secs() < WARNING_SECS && !warned {
+                    warned = true;
+                    ui[10].set_color(RED);
+                    announced.push((Text::new(&format!("{} SECONDS LEFT", WARNING_SECS), border_left + 12, 0, 20, Some(RED)), POPUP_TICKS));
                 }
-
             }
-            ticks = 0;
+            None => ui[10].change_text(&sprint::format_time(sprint::millis(&field))), // UPDATE TIME
+        }
+        if let Some(goal) = goal {
+            let done = if sprint.is_some() {field.lines} else {field.garbage_lines()};
+            goal_ui[1].change_text(&cast_with_capacity(goal.saturating_sub(done) as u64, 3)); // UPDATE LINES LEFT
+        }
+        if let (Some(splits), Some(best)) = (&splits, &best) {
+            let delta = splits.delta(best, sprint::millis(&field));
+            goal_ui[3].change_text(&delta.map_or("0.000".to_string(), sprint::format_delta)); // UPDATE DELTA
+        }
 
+        // MEASURE FPS
+        fps_frames += 1;
+        if fps_timer.elapsed() >= Duration::from_secs(1) {
+            window.set_title(&format!("NAME_HERE; Lines: {}; FPS: {}", field.lines, fps_frames));
+            fps_frames = 0;
+            fps_timer = Instant::now();
+        }
+
+        // LIMIT FRAME RATE
+        if !window.is_vsync() {
+            if let Some(rest) = frame_budget.checked_sub(frame_start.elapsed()) {
+                ::std::thread::sleep(rest);
+            }
         }
-        else { ticks += 1 }
     }
 
     Ok(())